@@ -0,0 +1,660 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// Arbitrary precision integer, sign and magnitude
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    // Base 2^32 digits, least significant first, without leading zeros.
+    // Zero has no digits and is never negative.
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let negative = negative && !digits.is_empty();
+        BigInt { negative, digits }
+    }
+
+    fn from_u64(negative: bool, mut n: u64) -> Self {
+        let mut digits = Vec::new();
+        while n > 0 {
+            digits.push(n as u32);
+            n >>= 32;
+        }
+        BigInt::from_parts(negative, digits)
+    }
+
+    // Bytes used for the digits
+    pub fn heap_size(&self) -> usize {
+        self.digits.len() * 4
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // Number of bits in the magnitude, 0 for zero
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(top) => self.digits.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    // The magnitude multiplied by 2^bits, sign kept
+    pub fn shift_left(&self, bits: u64) -> BigInt {
+        let mut digits = vec![0; (bits / 32) as usize];
+        digits.extend(shl(&self.digits, (bits % 32) as u32));
+        BigInt::from_parts(self.negative, digits)
+    }
+
+    // The magnitude divided by 2^bits, rounded towards zero, sign kept
+    pub fn shift_right(&self, bits: u64) -> BigInt {
+        let whole = (bits / 32).min(self.digits.len() as u64) as usize;
+        let digits = shr(&self.digits[whole..], (bits % 32) as u32);
+        BigInt::from_parts(self.negative, digits)
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.digits.first().is_none_or(|d| d % 2 == 0)
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.digits.clone())
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits.clone())
+    }
+
+    // The value as an i64, if it fits
+    pub fn to_i64(&self) -> Option<i64> {
+        let magnitude = self.magnitude_u64()?;
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    // The value as a u64, if it fits
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.negative {
+            return None;
+        }
+        self.magnitude_u64()
+    }
+
+    fn magnitude_u64(&self) -> Option<u64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &d| (acc << 32) | d as u64);
+        Some(magnitude)
+    }
+
+    // Rounded to the nearest float. The top 64 bits, with the lowest one set
+    // if anything nonzero was cut off below them, round the same way.
+    pub fn to_f64(&self) -> f64 {
+        let shift = self.bits().saturating_sub(64);
+        let mut top = self.shift_right(shift).magnitude_u64().unwrap();
+        let whole = (shift / 32) as usize;
+        let part = self
+            .digits
+            .get(whole)
+            .map_or(0, |d| d & ((1 << (shift % 32)) - 1));
+        if part != 0 || self.digits[..whole].iter().any(|&d| d != 0) {
+            top |= 1;
+        }
+        let magnitude = scale_by_power_of_two(top as f64, shift as i64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    // Parse decimal digits with an optional leading '-'
+    pub fn parse(text: &str) -> Option<BigInt> {
+        BigInt::parse_radix(text, 10)
+    }
+
+    // Parse digits in base 2 to 36 with an optional leading '-'
+    pub fn parse_radix(text: &str, radix: u32) -> Option<BigInt> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut magnitude = Vec::new();
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            mul_small_add(&mut magnitude, radix, digit);
+        }
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+
+    // Digits in base 2 to 36, lowercase, with a leading '-' if negative
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        // Divide off as many digits at once as fit in a u32
+        let mut chunk = radix;
+        let mut per_chunk = 1;
+        while let Some(next) = chunk.checked_mul(radix) {
+            chunk = next;
+            per_chunk += 1;
+        }
+        let mut digits = Vec::new();
+        let mut magnitude = self.digits.clone();
+        while !magnitude.is_empty() {
+            let mut rem = div_small(&mut magnitude, chunk);
+            for _ in 0..per_chunk {
+                digits.push(std::char::from_digit(rem % radix, radix).unwrap());
+                rem /= radix;
+            }
+        }
+        while digits.len() > 1 && digits.last() == Some(&'0') {
+            digits.pop();
+        }
+        if self.negative {
+            digits.push('-');
+        }
+        digits.iter().rev().collect()
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_mag(&self.digits, &other.digits));
+        }
+        match cmp_mag(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_mag(&other.digits, &self.digits))
+            }
+            _ => BigInt::from_parts(self.negative, sub_mag(&self.digits, &other.digits)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_mag(&self.digits, &other.digits),
+        )
+    }
+
+    // Quotient rounded towards zero and the remainder with the sign of self,
+    // None when dividing by zero
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_mag(&self.digits, &other.digits);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    // Quotient rounded towards negative infinity and the remainder with the
+    // sign of other
+    pub fn div_mod_floor(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        let (quotient, remainder) = self.div_rem(other)?;
+        if !remainder.is_zero() && remainder.negative != other.negative {
+            let one = BigInt::from(1);
+            Some((quotient.sub(&one), remainder.add(other)))
+        } else {
+            Some((quotient, remainder))
+        }
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    // Greatest common divisor, never negative. Lehmer's algorithm: while the
+    // numbers are large, as many Euclid steps as the top 63 bits decide are
+    // done on machine words and then applied to the whole numbers at once.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        if a < b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        while b.digits.len() > 2 {
+            let shift = a.bits() - 63;
+            let mut x = a.shift_right(shift).to_i64().unwrap() as i128;
+            let mut y = b.shift_right(shift).to_i64().unwrap() as i128;
+            let (mut p, mut q, mut r, mut s) = (1i128, 0i128, 0i128, 1i128);
+            // Stop as soon as the quotient could differ from the true one
+            while y + r != 0 && y + s != 0 {
+                let quotient = (x + p) / (y + r);
+                if quotient != (x + q) / (y + s) {
+                    break;
+                }
+                (p, r) = (r, p - quotient * r);
+                (q, s) = (s, q - quotient * s);
+                (x, y) = (y, x - quotient * y);
+            }
+
+            if q == 0 {
+                // Nothing was decided, take one full step
+                let (_, remainder) = a.div_rem(&b).unwrap();
+                a = b;
+                b = remainder;
+            } else {
+                let word = |n: i128| BigInt::from(n as i64);
+                let next_a = a.mul(&word(p)).add(&b.mul(&word(q)));
+                let next_b = a.mul(&word(r)).add(&b.mul(&word(s)));
+                a = next_a;
+                b = next_b;
+            }
+        }
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b).unwrap();
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        BigInt::from_u64(n < 0, n.unsigned_abs())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.digits, &other.digits),
+            (true, true) => cmp_mag(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_string_radix(10))
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &digit) in long.iter().enumerate() {
+        let sum = digit as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+// a - b where |a| >= |b|
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut diff = digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        result.push(diff as u32);
+    }
+    result
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    result
+}
+
+// digits = digits * factor + add
+fn mul_small_add(digits: &mut Vec<u32>, factor: u32, add: u32) {
+    let mut carry = add as u64;
+    for digit in digits.iter_mut() {
+        let t = *digit as u64 * factor as u64 + carry;
+        *digit = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+}
+
+// Divide in place, returning the remainder
+fn div_small(digits: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for digit in digits.iter_mut().rev() {
+        let t = (rem << 32) | *digit as u64;
+        *digit = (t / divisor as u64) as u32;
+        rem = t % divisor as u64;
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    rem as u32
+}
+
+// Long division of magnitudes (Knuth, algorithm D)
+fn div_rem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let mut quotient = a.to_vec();
+        let rem = div_small(&mut quotient, b[0]);
+        return (quotient, vec![rem]);
+    }
+
+    // Normalize so the top digit of the divisor has its high bit set
+    let shift = b.last().unwrap().leading_zeros();
+    let b = shl(b, shift);
+    let mut a = shl(a, shift);
+    a.push(0);
+
+    let n = b.len();
+    let m = a.len() - n;
+    let mut quotient = vec![0u32; m];
+    let top = b[n - 1] as u64;
+    let second = b[n - 2] as u64;
+
+    for j in (0..m).rev() {
+        // Estimate the quotient digit from the top two digits
+        let numerator = ((a[j + n] as u64) << 32) | a[j + n - 1] as u64;
+        let mut q = numerator / top;
+        let mut r = numerator % top;
+        while q >= 1 << 32 || q * second > ((r << 32) | a[j + n - 2] as u64) {
+            q -= 1;
+            r += top;
+            if r >= 1 << 32 {
+                break;
+            }
+        }
+
+        // Subtract q * b from the current window
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = q * b[i] as u64 + carry;
+            carry = product >> 32;
+            let diff = a[i + j] as i64 - (product & 0xffff_ffff) as i64 - borrow;
+            a[i + j] = diff as u32;
+            borrow = if diff < 0 { 1 } else { 0 };
+        }
+        let diff = a[j + n] as i64 - carry as i64 - borrow;
+        a[j + n] = diff as u32;
+
+        // The estimate was one too big, add back
+        if diff < 0 {
+            q -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = a[i + j] as u64 + b[i] as u64 + carry;
+                a[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            a[j + n] = a[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = q as u32;
+    }
+
+    a.truncate(n);
+    let remainder = shr(&a, shift);
+    (quotient, remainder)
+}
+
+// x * 2^exponent, in steps that can't overflow before the result does
+pub fn scale_by_power_of_two(mut x: f64, mut exponent: i64) -> f64 {
+    while exponent != 0 && x != 0.0 && x.is_finite() {
+        let step = exponent.clamp(-1000, 1000);
+        x *= 2f64.powi(step as i32);
+        exponent -= step;
+    }
+    x
+}
+
+fn shl(digits: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return digits.to_vec();
+    }
+    let mut result = Vec::with_capacity(digits.len() + 1);
+    let mut carry = 0;
+    for &digit in digits {
+        result.push((digit << shift) | carry);
+        carry = digit >> (32 - shift);
+    }
+    if carry > 0 {
+        result.push(carry);
+    }
+    result
+}
+
+fn shr(digits: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return digits.to_vec();
+    }
+    let mut result = vec![0; digits.len()];
+    for i in 0..digits.len() {
+        let high = digits.get(i + 1).map_or(0, |d| d << (32 - shift));
+        result[i] = (digits[i] >> shift) | high;
+    }
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text).unwrap()
+    }
+
+    fn hex(text: &str) -> BigInt {
+        BigInt::parse_radix(text, 16).unwrap()
+    }
+
+    #[test]
+    fn add_carries_into_a_new_digit() {
+        let max = hex("ffffffffffffffff");
+        assert_eq!(max.add(&BigInt::from(1)), hex("10000000000000000"));
+        assert_eq!(max.add(&max), hex("1fffffffffffffffe"));
+    }
+
+    #[test]
+    fn sub_borrows_across_digits() {
+        let top = hex("10000000000000000");
+        assert_eq!(top.sub(&BigInt::from(1)), hex("ffffffffffffffff"));
+        assert_eq!(BigInt::from(1).sub(&top), hex("-ffffffffffffffff"));
+        // Equal magnitudes give a zero that isn't negative
+        let zero = top.sub(&top);
+        assert!(zero.is_zero() && !zero.is_negative());
+        assert_eq!(zero, BigInt::from(0));
+    }
+
+    #[test]
+    fn mul_carries_between_digits() {
+        let max = hex("ffffffff");
+        assert_eq!(max.mul(&max), hex("fffffffe00000001"));
+        assert_eq!(
+            big("-123456789012345678901").mul(&big("98765432109876543210")),
+            big("-12193263113702179522473403443222511812210")
+        );
+    }
+
+    #[test]
+    fn div_rem_adds_back_when_the_estimate_is_too_big() {
+        let cases = [
+            (
+                "7fffffff800000000000000000000000",
+                "800000000000000000000001",
+                "fffffffe",
+                "7fffffffffffffff00000002",
+            ),
+            (
+                "800000000000000000000003",
+                "200000000000000000000001",
+                "3",
+                "200000000000000000000000",
+            ),
+            (
+                "7fff000080000000000000000000",
+                "80000000000000000001",
+                "fffe0000",
+                "7fffffffffff00020000",
+            ),
+        ];
+        for (a, b, quotient, remainder) in cases {
+            let (q, r) = hex(a).div_rem(&hex(b)).unwrap();
+            assert_eq!((q, r), (hex(quotient), hex(remainder)), "{} / {}", a, b);
+        }
+    }
+
+    #[test]
+    fn div_rem_truncates() {
+        let cases = [
+            (7, 2, 3, 1),
+            (-7, 2, -3, -1),
+            (7, -2, -3, 1),
+            (-7, -2, 3, -1),
+        ];
+        for (a, b, quotient, remainder) in cases {
+            let (q, r) = BigInt::from(a).div_rem(&BigInt::from(b)).unwrap();
+            assert_eq!((q, r), (BigInt::from(quotient), BigInt::from(remainder)));
+        }
+        assert!(BigInt::from(1).div_rem(&BigInt::from(0)).is_none());
+    }
+
+    #[test]
+    fn div_mod_floor_follows_the_divisor_sign() {
+        let cases = [
+            (7, 2, 3, 1),
+            (-7, 2, -4, 1),
+            (7, -2, -4, -1),
+            (-7, -2, 3, -1),
+            (-6, 2, -3, 0),
+            (6, -2, -3, 0),
+        ];
+        for (a, b, quotient, remainder) in cases {
+            let (q, r) = BigInt::from(a).div_mod_floor(&BigInt::from(b)).unwrap();
+            assert_eq!((q, r), (BigInt::from(quotient), BigInt::from(remainder)));
+        }
+
+        let a = big("-100000000000000000000000000001");
+        let (q, r) = a.div_mod_floor(&big("10000000000000000000")).unwrap();
+        assert_eq!((q, r), (big("-10000000001"), big("9999999999999999999")));
+    }
+
+    #[test]
+    fn gcd_is_never_negative() {
+        assert_eq!(BigInt::from(-12).gcd(&BigInt::from(18)), BigInt::from(6));
+        assert_eq!(BigInt::from(12).gcd(&BigInt::from(-18)), BigInt::from(6));
+        assert_eq!(BigInt::from(0).gcd(&BigInt::from(-5)), BigInt::from(5));
+        assert_eq!(BigInt::from(0).gcd(&BigInt::from(0)), BigInt::from(0));
+        let a = big("-340282366920938463463374607431768211456");
+        assert_eq!(a.gcd(&big("6000000000000000000000")), big("4194304"));
+    }
+
+    #[test]
+    fn gcd_of_large_numbers() {
+        // Many digits, so most steps are taken on the leading words
+        let two = BigInt::from(2);
+        let three = BigInt::from(3);
+        let a = two.pow(200).mul(&three.pow(50));
+        let b = BigInt::from(6).pow(100).mul(&BigInt::from(7));
+        assert_eq!(a.gcd(&b), two.pow(100).mul(&three.pow(50)));
+        let fib = |n: usize| {
+            let (mut x, mut y) = (BigInt::from(0), BigInt::from(1));
+            for _ in 0..n {
+                (x, y) = (y.clone(), x.add(&y));
+            }
+            x
+        };
+        // Consecutive Fibonacci numbers take the most Euclid steps
+        assert_eq!(fib(1000).gcd(&fib(999)), BigInt::from(1));
+        assert_eq!(fib(1000).gcd(&fib(500)), fib(500));
+    }
+
+    #[test]
+    fn radix_text_round_trips() {
+        let n = big("-98765432109876543210987654321");
+        for radix in [2, 10, 16, 36] {
+            let text = n.to_string_radix(radix);
+            assert_eq!(BigInt::parse_radix(&text, radix), Some(n.clone()));
+        }
+        assert_eq!(hex("100000000").to_string(), "4294967296");
+    }
+
+    #[test]
+    fn to_f64_rounds_to_nearest() {
+        // 2^53 + 1 is a tie, which goes to the even 2^53
+        assert_eq!(hex("20000000000001").to_f64(), 9007199254740992.0);
+        // Just over half way only shows in bits below the top 64
+        let above_half = hex("10000000000000800000000000000001");
+        assert_eq!(above_half.to_f64(), 2f64.powi(124) + 2f64.powi(72));
+        assert_eq!(BigInt::from(10).pow(400).to_f64(), f64::INFINITY);
+        assert_eq!(BigInt::from(-3).to_f64(), -3.0);
+    }
+}
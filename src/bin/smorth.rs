@@ -0,0 +1,128 @@
+use smorth::{Error, Interpreter};
+use std::env;
+use std::io::{self, Write};
+
+// Calls shown for an error before the rest are left out
+const MAX_BACKTRACE: usize = 20;
+
+fn main() {
+    let mut filename = None;
+    let mut sandbox = false;
+    let mut seed = None;
+
+    // Directories to search for included files
+    let mut search_path = Vec::new();
+    if let Some(paths) = env::var_os("SMORTH_PATH") {
+        search_path.extend(env::split_paths(&paths));
+    }
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-I" => match args.next() {
+                Some(dir) => search_path.push(dir.into()),
+                None => usage(),
+            },
+            "--sandbox" => sandbox = true,
+            "--seed" => match args.next().and_then(|n| n.parse::<u64>().ok()) {
+                Some(n) => seed = Some(n),
+                None => usage(),
+            },
+            _ if filename.is_none() => filename = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    let mut interp = if sandbox {
+        Interpreter::sandboxed()
+    } else {
+        Interpreter::new()
+    };
+    if let Some(seed) = seed {
+        interp.set_seed(seed);
+    }
+    for dir in search_path {
+        interp.add_search_path(dir);
+    }
+
+    // If a file is provided, run it
+    if let Some(filename) = filename {
+        if let Err(e) = interp.eval_file(&filename) {
+            eprint!("{}", report(&e));
+            std::process::exit(1);
+        }
+        interp.show_stack();
+        return;
+    }
+    repl(&mut interp);
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: smorth [--sandbox] [--seed n] [-I dir]... [file]");
+    std::process::exit(2);
+}
+
+fn repl(interp: &mut Interpreter) {
+    println!("Smorth | Stack Language");
+    println!("Type 'bye' to exit.");
+
+    // A line that fails leaves nothing half done behind
+    interp.set_transactional(true);
+
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+        let input = input.trim();
+
+        if input == "bye" {
+            break;
+        }
+        if input.is_empty() {
+            continue;
+        }
+
+        match interp.eval(input) {
+            Ok(_) => interp.show_stack(),
+            Err(e) => print!("{}", report(&e)),
+        }
+    }
+}
+
+// Render an error with the offending line underlined, followed by the backtrace.
+// The line comes from the source the error happened in, even an earlier input.
+fn report(error: &Error) -> String {
+    let mut out = format!("Error: {}\n", error);
+
+    if let Some(span) = error.span()
+        && let Some(source) = error.source()
+        && let Some(line) = source.lines().nth(span.line - 1)
+    {
+        let number = span.line.to_string();
+        let gutter = " ".repeat(number.len());
+        out += &format!("{} |\n", gutter);
+        out += &format!("{} | {}\n", number, line);
+        out += &format!(
+            "{} | {}{}\n",
+            gutter,
+            " ".repeat(span.column - 1),
+            "^".repeat(span.len.max(1))
+        );
+    }
+
+    // Deep recursion would print thousands of identical lines
+    let backtrace = error.backtrace();
+    for frame in backtrace.iter().take(MAX_BACKTRACE) {
+        if frame.omitted > 0 {
+            out += &format!("  ... {} tail calls omitted\n", frame.omitted);
+        }
+        out += &format!("  called from {}\n", frame);
+    }
+    if backtrace.len() > MAX_BACKTRACE {
+        out += &format!("  ... {} more\n", backtrace.len() - MAX_BACKTRACE);
+    }
+
+    out
+}
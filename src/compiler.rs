@@ -0,0 +1,480 @@
+use crate::bigint::BigInt;
+use crate::decimal::Decimal;
+use crate::error::{Error, Result};
+use crate::interpreter::{Interpreter, NativeFn, Word};
+use crate::rational::Rational;
+use crate::tokenizer::{Token, TokenKind};
+use crate::types::Type;
+use std::fmt;
+use std::rc::Rc;
+
+// A single resolved instruction of a compiled word
+#[derive(Clone)]
+pub enum Op {
+    Push(Type),
+    Native(NativeFn),
+    // Call a word by its index in `Interpreter::words`
+    Call(usize),
+    // Call a word that did not exist yet when compiling, resolved on every call
+    CallName(String),
+    Load(String),
+    Store(String),
+    LoadLocal(usize),
+    StoreLocal(usize),
+    Jump(usize),
+    JumpIfFalse(usize),
+    // limit start do
+    Do,
+    // Like Do, but jumps past the loop when start == limit
+    QDo(usize),
+    // Jump back to the loop body while the index has not reached the limit
+    Loop(usize),
+    PlusLoop(usize),
+    // Drop the innermost loop and jump past it
+    Leave(usize),
+    // Leave the loop around the try block this is in
+    LeaveBlock,
+    I,
+    J,
+    // try body catch handler finally cleanup end
+    Try {
+        body: Rc<Code>,
+        handler: Option<Rc<Code>>,
+        cleanup: Option<Rc<Code>>,
+        // Where a leave in one of the parts continues, None when the loop
+        // is outside this code too
+        leave: Option<usize>,
+    },
+}
+
+// Compiled code, each instruction remembers the token it came from for error reporting
+pub struct Code {
+    pub ops: Vec<Op>,
+    pub tokens: Vec<Token>,
+    // What was compiled
+    pub source: Vec<Token>,
+    // Number of locals, taken off the stack when the code starts
+    pub locals: usize,
+}
+
+impl Code {
+    fn push(&mut self, op: Op, token: &Token) {
+        self.ops.push(op);
+        self.tokens.push(token.clone());
+    }
+
+    fn len(&self) -> usize {
+        self.ops.len()
+    }
+}
+
+// Shows the source of the code, e.g. [: dup * ;]
+impl fmt::Debug for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[:")?;
+        for token in &self.source {
+            match token.kind {
+                TokenKind::Word => write!(f, " {}", token.text)?,
+                TokenKind::Str => write!(f, " \"{}\"", token.text)?,
+            }
+        }
+        write!(f, " ;]")
+    }
+}
+
+// Words that open a control structure
+pub const OPENERS: &[&str] = &["if", "do", "?do", "begin", "[:", "try"];
+
+// Every word compiled into control flow instead of a dictionary call
+pub const CONTROL_WORDS: &[&str] = &[
+    "if", "else", "then", "do", "?do", "loop", "+loop", "leave", "i", "j", "begin", "until",
+    "while", "repeat", "[:", ";]", "try", "catch", "finally", "end",
+];
+
+// Vocabulary words that only work at the top level
+pub const DIRECTIVES: &[&str] = &["vocabulary", "in", "also", "only", "private", "public"];
+
+// Words that close a control structure
+pub const CLOSERS: &[&str] = &["then", "loop", "+loop", "until", "repeat", ";]", "end"];
+
+// Open control structures while compiling, holding the positions to patch
+enum Control {
+    If(usize),
+    Else(usize),
+    Do { start: usize, leaves: Vec<usize> },
+    Begin(usize),
+    While { begin: usize, cond: usize },
+}
+
+impl Control {
+    fn name(&self) -> &'static str {
+        match self {
+            Control::If(_) | Control::Else(_) => "if",
+            Control::Do { .. } => "do",
+            Control::Begin(_) | Control::While { .. } => "begin",
+        }
+    }
+}
+
+// What code can refer to besides the dictionary
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    // Name and index of the word being defined, so it can call itself
+    this_word: Option<(&'a str, usize)>,
+    // Locals of the word being defined, by slot
+    locals: &'a [String],
+    // Quotations run on their own and can't see the locals
+    in_quotation: bool,
+    // Inside a try block within a do loop, so leave can end the loop
+    in_loop: bool,
+}
+
+// Resolve every token once into an instruction.
+// `this_word` is the name and index of the word being defined, so it can call itself.
+pub fn compile(
+    interp: &Interpreter,
+    tokens: &[Token],
+    this_word: Option<(&str, usize)>,
+) -> Result<Code> {
+    // A definition can start with its locals: { a b -- c }
+    let (locals, body) = match (this_word, tokens.first()) {
+        (Some(_), Some(first)) if first.is("{") => parse_locals(tokens)?,
+        _ => (Vec::new(), tokens),
+    };
+    let scope = Scope {
+        this_word,
+        locals: &locals,
+        in_quotation: false,
+        in_loop: false,
+    };
+
+    let mut code = compile_scoped(interp, body, scope)?;
+    code.locals = locals.len();
+    code.source = tokens.to_vec();
+    Ok(code)
+}
+
+// Split `{ a b -- c }` off the front of a definition. The names after --
+// only document the results.
+fn parse_locals(tokens: &[Token]) -> Result<(Vec<String>, &[Token])> {
+    let mut locals: Vec<String> = Vec::new();
+    let mut results = false;
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        if token.is("}") {
+            return Ok((locals, &tokens[i + 1..]));
+        }
+        if token.is("--") {
+            results = true;
+        } else if results {
+            continue;
+        } else if token.kind == TokenKind::Str || token.text.ends_with(['@', '!']) {
+            let message = format!("'{}' is not a valid local name", token.text);
+            return Err(Error::syntax(message).at(token));
+        } else if locals.contains(&token.text) {
+            let message = format!("Local '{}' declared twice", token.text);
+            return Err(Error::syntax(message).at(token));
+        } else {
+            locals.push(token.text.clone());
+        }
+    }
+    Err(Error::syntax("Unterminated '{'").at(&tokens[0]))
+}
+
+fn compile_scoped(interp: &Interpreter, tokens: &[Token], scope: Scope) -> Result<Code> {
+    let mut code = Code {
+        ops: Vec::new(),
+        tokens: Vec::new(),
+        source: tokens.to_vec(),
+        locals: 0,
+    };
+    // Open structures with the token that opened them
+    let mut open: Vec<(Control, &Token)> = Vec::new();
+
+    let mut rest = tokens.iter();
+    while let Some(token) = rest.next() {
+        if token.kind == TokenKind::Str {
+            code.push(Op::Push(Type::String(token.text.clone())), token);
+            continue;
+        }
+
+        let unmatched = |expected: &str| {
+            Error::syntax(format!("'{}' without matching '{}'", token.text, expected)).at(token)
+        };
+
+        match token.text.as_str() {
+            "if" => {
+                open.push((Control::If(code.len()), token));
+                code.push(Op::JumpIfFalse(0), token);
+            }
+            "else" => {
+                let Some((Control::If(cond), _)) = open.pop() else {
+                    return Err(unmatched("if"));
+                };
+                open.push((Control::Else(code.len()), token));
+                code.push(Op::Jump(0), token);
+                code.ops[cond] = Op::JumpIfFalse(code.len());
+            }
+            "then" => match open.pop() {
+                Some((Control::If(cond), _)) => code.ops[cond] = Op::JumpIfFalse(code.len()),
+                Some((Control::Else(jump), _)) => code.ops[jump] = Op::Jump(code.len()),
+                _ => return Err(unmatched("if")),
+            },
+            "do" | "?do" => {
+                let start = code.len();
+                let leaves = Vec::new();
+                open.push((Control::Do { start, leaves }, token));
+                code.push(if token.is("do") { Op::Do } else { Op::QDo(0) }, token);
+            }
+            "loop" | "+loop" => {
+                let Some((Control::Do { start, leaves }, _)) = open.pop() else {
+                    return Err(unmatched("do"));
+                };
+                let op = if token.is("loop") {
+                    Op::Loop(start + 1)
+                } else {
+                    Op::PlusLoop(start + 1)
+                };
+                code.push(op, token);
+                let end = code.len();
+                if let Op::QDo(target) = &mut code.ops[start] {
+                    *target = end;
+                }
+                for leave in leaves {
+                    match &mut code.ops[leave] {
+                        Op::Try { leave, .. } => *leave = Some(end),
+                        op => *op = Op::Leave(end),
+                    }
+                }
+            }
+            "leave" => match innermost_do(&mut open) {
+                Some(leaves) => {
+                    leaves.push(code.len());
+                    code.push(Op::Leave(0), token);
+                }
+                None if scope.in_loop => code.push(Op::LeaveBlock, token),
+                None => return Err(Error::syntax("'leave' outside of a 'do' loop").at(token)),
+            },
+            "i" => code.push(Op::I, token),
+            "j" => code.push(Op::J, token),
+            "begin" => open.push((Control::Begin(code.len()), token)),
+            "until" => {
+                let Some((Control::Begin(begin), _)) = open.pop() else {
+                    return Err(unmatched("begin"));
+                };
+                code.push(Op::JumpIfFalse(begin), token);
+            }
+            "while" => {
+                let Some((Control::Begin(begin), _)) = open.pop() else {
+                    return Err(unmatched("begin"));
+                };
+                let cond = code.len();
+                open.push((Control::While { begin, cond }, token));
+                code.push(Op::JumpIfFalse(0), token);
+            }
+            "repeat" => {
+                let Some((Control::While { begin, cond }, _)) = open.pop() else {
+                    return Err(unmatched("while"));
+                };
+                code.push(Op::Jump(begin), token);
+                code.ops[cond] = Op::JumpIfFalse(code.len());
+            }
+            "[:" => {
+                // Collect the body up to the matching ;]
+                let mut body = Vec::new();
+                let mut depth = 0;
+                loop {
+                    let Some(next) = rest.next() else {
+                        return Err(Error::syntax("Unterminated '[:'").at(token));
+                    };
+                    if next.is("[:") {
+                        depth += 1;
+                    } else if next.is(";]") {
+                        if depth == 0 {
+                            break;
+                        }
+                        depth -= 1;
+                    }
+                    body.push(next.clone());
+                }
+
+                let scope = Scope {
+                    in_quotation: true,
+                    in_loop: false,
+                    ..scope
+                };
+                let quotation = compile_scoped(interp, &body, scope)?;
+                code.push(Op::Push(Type::Quotation(Rc::new(quotation))), token);
+            }
+            ";]" => return Err(unmatched("[:")),
+            "try" => {
+                // Collect the parts up to the matching end
+                let mut parts: Vec<(&str, Vec<Token>)> = vec![("try", Vec::new())];
+                let mut depth = 0;
+                loop {
+                    let Some(next) = rest.next() else {
+                        return Err(Error::syntax("Unterminated 'try'").at(token));
+                    };
+                    if next.is("try") {
+                        depth += 1;
+                    } else if next.is("end") {
+                        if depth == 0 {
+                            break;
+                        }
+                        depth -= 1;
+                    } else if depth == 0 && (next.is("catch") || next.is("finally")) {
+                        let last = parts.last().unwrap().0;
+                        if last == "finally" || last == next.text {
+                            let message = format!("'{}' out of place in 'try'", next.text);
+                            return Err(Error::syntax(message).at(next));
+                        }
+                        parts.push((&next.text, Vec::new()));
+                        continue;
+                    }
+                    parts.last_mut().unwrap().1.push(next.clone());
+                }
+                if parts.len() == 1 {
+                    let message = "'try' needs a 'catch' or 'finally'";
+                    return Err(Error::syntax(message).at(token));
+                }
+
+                // A leave in the parts ends the loop the try is in
+                let in_loop = innermost_do(&mut open).is_some();
+                let scope = Scope {
+                    in_loop: in_loop || scope.in_loop,
+                    ..scope
+                };
+                let part = |name: &str| -> Result<Option<Rc<Code>>> {
+                    match parts.iter().find(|(n, _)| *n == name) {
+                        Some((_, tokens)) => {
+                            Ok(Some(Rc::new(compile_scoped(interp, tokens, scope)?)))
+                        }
+                        None => Ok(None),
+                    }
+                };
+                let op = Op::Try {
+                    body: part("try")?.unwrap(),
+                    handler: part("catch")?,
+                    cleanup: part("finally")?,
+                    leave: None,
+                };
+                if let Some(leaves) = innermost_do(&mut open) {
+                    leaves.push(code.len());
+                }
+                code.push(op, token);
+            }
+            "catch" | "finally" | "end" => return Err(unmatched("try")),
+            word if DIRECTIVES.contains(&word) => {
+                let message = format!("'{}' can only be used at the top level", word);
+                return Err(Error::syntax(message).at(token));
+            }
+            _ => {
+                let op = compile_word(interp, &token.text, scope).map_err(|e| e.at(token))?;
+                code.push(op, token);
+            }
+        }
+    }
+
+    if let Some((control, token)) = open.last() {
+        let message = format!("Unterminated '{}'", control.name());
+        return Err(Error::syntax(message).at(token));
+    }
+
+    Ok(code)
+}
+
+// Where the leaves of the innermost open do loop are collected
+fn innermost_do<'a>(open: &'a mut [(Control, &Token)]) -> Option<&'a mut Vec<usize>> {
+    open.iter_mut().rev().find_map(|(c, _)| match c {
+        Control::Do { leaves, .. } => Some(leaves),
+        _ => None,
+    })
+}
+
+fn compile_word(interp: &Interpreter, token: &str, scope: Scope) -> Result<Op> {
+    // Variable storage and retrieval, locals shadow globals
+    if (token.ends_with("!") || token.ends_with("@")) && token.len() > 1 {
+        let name = &token[..token.len() - 1];
+        if let Some(slot) = scope.locals.iter().position(|local| local == name) {
+            if scope.in_quotation {
+                let message = format!("Local '{}' cannot be used inside a quotation", name);
+                return Err(Error::syntax(message));
+            }
+            return Ok(if token.ends_with("!") {
+                Op::StoreLocal(slot)
+            } else {
+                Op::LoadLocal(slot)
+            });
+        }
+        return Ok(if token.ends_with("!") {
+            Op::Store(name.to_string())
+        } else {
+            Op::Load(name.to_string())
+        });
+    }
+
+    // Decimals, 0.1d, or 0.1 after decimal-literals
+    if let Some(d) = token.strip_suffix('d').and_then(Decimal::parse) {
+        return Ok(Op::Push(Type::Decimal(d)));
+    }
+    if interp.numbers.decimal_literals
+        && token.contains('.')
+        && let Some(d) = Decimal::parse(token)
+    {
+        return Ok(Op::Push(Type::Decimal(d)));
+    }
+
+    // Floats
+    if token.contains('.')
+        && let Ok(f) = token.parse::<f64>()
+    {
+        return Ok(Op::Push(Type::Float(f)));
+    }
+
+    // Integers
+    if let Ok(n) = token.parse::<i64>() {
+        return Ok(Op::Push(Type::Int(n)));
+    }
+    if let Some(n) = parse_integer(token) {
+        return Ok(Op::Push(Type::from(n)));
+    }
+
+    // Rationals, 1/3
+    if let Some(r) = Rational::parse(token) {
+        let r = r.ok_or_else(|| Error::syntax("Zero denominator"))?;
+        return Ok(Op::Push(Type::from(r)));
+    }
+
+    // Dictionary lookup
+    if let Some((name, index)) = scope.this_word
+        && name == token
+    {
+        return Ok(Op::Call(index));
+    }
+    Ok(match interp.dictionary.lookup(token) {
+        Some(index) => match &interp.words[index] {
+            Word::Native(func) => Op::Native(func.clone()),
+            Word::UserDefined(_) => Op::Call(index),
+        },
+        None => Op::CallName(token.to_string()),
+    })
+}
+
+// Big or underscored integers and ones in other bases: 1_000_000, 0x1F,
+// 0b1010, 0o17
+fn parse_integer(token: &str) -> Option<BigInt> {
+    let (sign, rest) = match token.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", token),
+    };
+    let (radix, digits) = match rest.get(..2) {
+        Some("0x" | "0X") => (16, &rest[2..]),
+        Some("0b" | "0B") => (2, &rest[2..]),
+        Some("0o" | "0O") => (8, &rest[2..]),
+        _ => (10, rest),
+    };
+    // Underscores only go between digits
+    if digits.starts_with(['_', '-']) || digits.ends_with('_') || digits.contains("__") {
+        return None;
+    }
+    BigInt::parse_radix(&format!("{}{}", sign, digits.replace('_', "")), radix)
+}
@@ -0,0 +1,378 @@
+use crate::bigint::BigInt;
+use crate::rational::Rational;
+use std::cmp::Ordering;
+use std::fmt;
+
+// How results with more digits than the scale allows are rounded
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Rounding {
+    // To the nearest, ties to the even neighbour
+    #[default]
+    HalfEven,
+    // To the nearest, ties away from zero
+    HalfUp,
+    Floor,
+    Ceiling,
+}
+
+impl Rounding {
+    pub fn from_name(name: &str) -> Option<Rounding> {
+        match name {
+            "half-even" => Some(Rounding::HalfEven),
+            "half-up" => Some(Rounding::HalfUp),
+            "floor" => Some(Rounding::Floor),
+            "ceiling" => Some(Rounding::Ceiling),
+            _ => None,
+        }
+    }
+}
+
+// Base 10 fixed-point number, mantissa / 10^scale. Keeps its scale, so 1.50
+// stays 1.50, but compares by value.
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    mantissa: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: BigInt, scale: u32) -> Decimal {
+        Decimal { mantissa, scale }
+    }
+
+    // Parse digits with an optional '-' and decimal point, like -12.50
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let digits = whole.strip_prefix('-').unwrap_or(whole);
+        if digits.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let mantissa = BigInt::parse(&format!("{}{}", whole, fraction))?;
+        Some(Decimal::new(mantissa, fraction.len() as u32))
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+
+    // Bytes used for the digits
+    pub fn heap_size(&self) -> usize {
+        self.mantissa.heap_size()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Goes through the text to get the closest float
+        self.to_string().parse().unwrap()
+    }
+
+    pub fn to_rational(&self) -> Rational {
+        Rational::new(self.mantissa.clone(), pow10(self.scale)).unwrap()
+    }
+
+    // Mantissa at a scale at least as large as our own
+    fn mantissa_at(&self, scale: u32) -> BigInt {
+        self.mantissa.mul(&pow10(scale - self.scale))
+    }
+
+    // At most `scale` digits after the point
+    pub fn round(&self, scale: u32, rounding: Rounding) -> Decimal {
+        if self.scale <= scale {
+            return self.clone();
+        }
+        let divisor = pow10(self.scale - scale);
+        Decimal::new(div_round(&self.mantissa, &divisor, rounding), scale)
+    }
+
+    // Exactly `scale` digits after the point, for display
+    pub fn with_scale(&self, scale: u32, rounding: Rounding) -> Decimal {
+        if self.scale < scale {
+            Decimal::new(self.mantissa_at(scale), scale)
+        } else {
+            self.round(scale, rounding)
+        }
+    }
+
+    pub fn add(&self, other: &Decimal) -> Decimal {
+        let scale = self.scale.max(other.scale);
+        let mantissa = self.mantissa_at(scale).add(&other.mantissa_at(scale));
+        Decimal::new(mantissa, scale)
+    }
+
+    pub fn sub(&self, other: &Decimal) -> Decimal {
+        let negated = Decimal::new(other.mantissa.neg(), other.scale);
+        self.add(&negated)
+    }
+
+    pub fn mul(&self, other: &Decimal) -> Decimal {
+        let mantissa = self.mantissa.mul(&other.mantissa);
+        Decimal::new(mantissa, self.scale + other.scale)
+    }
+
+    // Quotient with `scale` digits after the point, None when dividing by zero
+    pub fn div(&self, other: &Decimal, scale: u32, rounding: Rounding) -> Option<Decimal> {
+        if other.mantissa.is_zero() {
+            return None;
+        }
+        // (a / 10^sa) / (b / 10^sb) = a * 10^(sb + scale) / (b * 10^sa) / 10^scale
+        let numerator = self.mantissa.mul(&pow10(other.scale + scale));
+        let denominator = other.mantissa.mul(&pow10(self.scale));
+        let quotient = Decimal::new(div_round(&numerator, &denominator, rounding), scale);
+        // Exact quotients don't need all the digits: 1.00 / 4 is 0.25
+        Some(quotient.trim(self.scale.max(other.scale)))
+    }
+
+    // Drop trailing zeros after the point, keeping at least `scale` digits
+    fn trim(mut self, scale: u32) -> Decimal {
+        let ten = BigInt::from(10);
+        while self.scale > scale {
+            let (quotient, remainder) = self.mantissa.div_rem(&ten).unwrap();
+            if !remainder.is_zero() {
+                break;
+            }
+            self.mantissa = quotient;
+            self.scale -= 1;
+        }
+        self
+    }
+
+    // None when the scale of the result doesn't fit
+    pub fn pow(&self, exponent: u32) -> Option<Decimal> {
+        let scale = self.scale.checked_mul(exponent)?;
+        Some(Decimal::new(self.mantissa.pow(exponent), scale))
+    }
+
+    // Nearest decimal with at most `scale` digits after the point
+    pub fn from_rational(r: &Rational, scale: u32, rounding: Rounding) -> Decimal {
+        let numerator = r.numerator().mul(&pow10(scale));
+        Decimal::new(div_round(&numerator, r.denominator(), rounding), scale).trim(0)
+    }
+}
+
+impl From<BigInt> for Decimal {
+    fn from(n: BigInt) -> Self {
+        Decimal::new(n, 0)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.mantissa_at(scale).cmp(&other.mantissa_at(scale))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.abs().to_string();
+        let scale = self.scale as usize;
+        if self.mantissa.is_negative() {
+            write!(f, "-")?;
+        }
+        if scale == 0 {
+            return write!(f, "{}", digits);
+        }
+        // Pad so there is at least one digit before the point
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}.{}", whole, fraction)
+    }
+}
+
+fn pow10(exponent: u32) -> BigInt {
+    BigInt::from(10).pow(exponent)
+}
+
+// n / d rounded to a whole number
+fn div_round(n: &BigInt, d: &BigInt, rounding: Rounding) -> BigInt {
+    let (n, d) = if d.is_negative() {
+        (n.neg(), d.neg())
+    } else {
+        (n.clone(), d.clone())
+    };
+    let (quotient, remainder) = n.div_rem(&d).unwrap();
+    if remainder.is_zero() {
+        return quotient;
+    }
+
+    // Away from zero is towards the sign of the exact result
+    let away = if n.is_negative() {
+        quotient.sub(&BigInt::from(1))
+    } else {
+        quotient.add(&BigInt::from(1))
+    };
+    let half = remainder.abs().mul(&BigInt::from(2)).cmp(&d);
+    let round_away = match rounding {
+        Rounding::Floor => n.is_negative(),
+        Rounding::Ceiling => !n.is_negative(),
+        Rounding::HalfUp => half != Ordering::Less,
+        Rounding::HalfEven => match half {
+            Ordering::Less => false,
+            Ordering::Greater => true,
+            Ordering::Equal => !quotient.is_even(),
+        },
+    };
+    if round_away { away } else { quotient }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    fn rounded(n: i64, d: i64, rounding: Rounding) -> i64 {
+        div_round(&BigInt::from(n), &BigInt::from(d), rounding)
+            .to_i64()
+            .unwrap()
+    }
+
+    #[test]
+    fn div_round_in_each_mode() {
+        // n, d, half-even, half-up, floor, ceiling
+        let cases = [
+            (5, 2, 2, 3, 2, 3),
+            (7, 2, 4, 4, 3, 4),
+            (-5, 2, -2, -3, -3, -2),
+            (-7, 2, -4, -4, -4, -3),
+            (5, -2, -2, -3, -3, -2),
+            (-5, -2, 2, 3, 2, 3),
+            (7, 3, 2, 2, 2, 3),
+            (8, 3, 3, 3, 2, 3),
+            (-8, 3, -3, -3, -3, -2),
+            (6, 3, 2, 2, 2, 2),
+        ];
+        for (n, d, half_even, half_up, floor, ceiling) in cases {
+            assert_eq!(
+                rounded(n, d, Rounding::HalfEven),
+                half_even,
+                "{} / {}",
+                n,
+                d
+            );
+            assert_eq!(rounded(n, d, Rounding::HalfUp), half_up, "{} / {}", n, d);
+            assert_eq!(rounded(n, d, Rounding::Floor), floor, "{} / {}", n, d);
+            assert_eq!(rounded(n, d, Rounding::Ceiling), ceiling, "{} / {}", n, d);
+        }
+    }
+
+    #[test]
+    fn rounding_names() {
+        assert_eq!(Rounding::from_name("half-up"), Some(Rounding::HalfUp));
+        assert_eq!(Rounding::from_name("ceiling"), Some(Rounding::Ceiling));
+        assert_eq!(Rounding::from_name("up"), None);
+    }
+
+    #[test]
+    fn parse_and_display_keep_the_scale() {
+        assert_eq!(decimal("1.50").to_string(), "1.50");
+        assert_eq!(decimal("-0.05").to_string(), "-0.05");
+        assert_eq!(decimal("12").scale(), 0);
+        assert!(Decimal::parse("1.5x").is_none());
+        assert!(Decimal::parse(".5").is_none());
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        assert_eq!(decimal("0.1").add(&decimal("0.2")).to_string(), "0.3");
+        assert_eq!(decimal("1.50").sub(&decimal("2")).to_string(), "-0.50");
+        assert_eq!(decimal("19.99").mul(&decimal("3")).to_string(), "59.97");
+        assert_eq!(decimal("1.50"), decimal("1.5"));
+        assert!(decimal("-0.1") < decimal("0.01"));
+    }
+
+    #[test]
+    fn div_rounds_and_trims() {
+        let third = decimal("1")
+            .div(&decimal("3"), 5, Rounding::HalfEven)
+            .unwrap();
+        assert_eq!(third.to_string(), "0.33333");
+        let two_thirds = decimal("-2")
+            .div(&decimal("3"), 2, Rounding::Floor)
+            .unwrap();
+        assert_eq!(two_thirds.to_string(), "-0.67");
+        // Exact quotients keep only the digits they need, at least the operands' scale
+        let quarter = decimal("1.00")
+            .div(&decimal("4"), 28, Rounding::HalfEven)
+            .unwrap();
+        assert_eq!(quarter.to_string(), "0.25");
+        let whole = decimal("1.00")
+            .div(&decimal("0.5"), 28, Rounding::HalfEven)
+            .unwrap();
+        assert_eq!(whole.to_string(), "2.00");
+        assert!(
+            decimal("1")
+                .div(&decimal("0.0"), 2, Rounding::HalfEven)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn round_and_with_scale() {
+        assert_eq!(
+            decimal("2.345").round(2, Rounding::HalfEven).to_string(),
+            "2.34"
+        );
+        assert_eq!(
+            decimal("2.345").round(2, Rounding::HalfUp).to_string(),
+            "2.35"
+        );
+        assert_eq!(
+            decimal("-2.341").round(2, Rounding::Floor).to_string(),
+            "-2.35"
+        );
+        assert_eq!(
+            decimal("2.3").round(2, Rounding::HalfEven).to_string(),
+            "2.3"
+        );
+        assert_eq!(
+            decimal("2.3").with_scale(3, Rounding::HalfEven).to_string(),
+            "2.300"
+        );
+    }
+
+    #[test]
+    fn from_rational_rounds_to_the_scale() {
+        let r = Rational::new(BigInt::from(2), BigInt::from(3)).unwrap();
+        assert_eq!(
+            Decimal::from_rational(&r, 3, Rounding::HalfEven).to_string(),
+            "0.667"
+        );
+        assert_eq!(
+            Decimal::from_rational(&r, 3, Rounding::Floor).to_string(),
+            "0.666"
+        );
+        let half = Rational::new(BigInt::from(1), BigInt::from(2)).unwrap();
+        assert_eq!(
+            Decimal::from_rational(&half, 28, Rounding::HalfEven).to_string(),
+            "0.5"
+        );
+        assert_eq!(
+            decimal("0.25").to_rational(),
+            Rational::new(BigInt::from(1), BigInt::from(4)).unwrap()
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+
+// Vocabulary holding the builtin words
+pub const CORE: &str = "core";
+
+// Vocabulary definitions go into unless told otherwise
+pub const USER: &str = "user";
+
+// A named group of words
+#[derive(Clone, Default)]
+pub struct Vocabulary {
+    // Name -> index into `Interpreter::words`
+    words: HashMap<String, usize>,
+    // Only visible while this vocabulary is the current one
+    private: HashSet<String>,
+}
+
+// Maps names to words through vocabularies
+#[derive(Clone)]
+pub struct Dictionary {
+    vocabularies: HashMap<String, Vocabulary>,
+    // Vocabularies searched for unqualified names, the last one first
+    search_order: Vec<String>,
+    // Vocabulary new definitions go into, searched before the search order
+    // while it is current
+    current: String,
+    // Whether new definitions are private
+    private: bool,
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        let mut dictionary = Dictionary {
+            vocabularies: HashMap::new(),
+            search_order: Vec::new(),
+            current: CORE.to_string(),
+            private: false,
+        };
+        dictionary.create(CORE);
+        dictionary.create(USER);
+        dictionary.search_order = vec![CORE.to_string(), USER.to_string()];
+        dictionary
+    }
+
+    pub fn exists(&self, vocabulary: &str) -> bool {
+        self.vocabularies.contains_key(vocabulary)
+    }
+
+    // Create a vocabulary, doing nothing if it already exists
+    pub fn create(&mut self, vocabulary: &str) {
+        self.vocabularies.entry(vocabulary.to_string()).or_default();
+    }
+
+    // Make a vocabulary the one definitions go into, the search order stays
+    pub fn set_current(&mut self, vocabulary: &str) {
+        self.current = vocabulary.to_string();
+        self.private = false;
+    }
+
+    // Search a vocabulary before all others
+    pub fn also(&mut self, vocabulary: &str) {
+        self.search_order.retain(|v| v != vocabulary);
+        self.search_order.push(vocabulary.to_string());
+    }
+
+    // Search nothing but the builtins and the current vocabulary
+    pub fn only(&mut self) {
+        self.search_order = vec![CORE.to_string()];
+        if self.current != CORE {
+            self.search_order.push(self.current.clone());
+        }
+    }
+
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+    }
+
+    // Add a word to the current vocabulary
+    pub fn insert(&mut self, name: &str, index: usize) {
+        let private = self.private;
+        let vocabulary = self.vocabularies.get_mut(&self.current).unwrap();
+        vocabulary.words.insert(name.to_string(), index);
+        if private {
+            vocabulary.private.insert(name.to_string());
+        } else {
+            vocabulary.private.remove(name);
+        }
+    }
+
+    pub fn remove(&mut self, vocabulary: &str, name: &str) {
+        if let Some(vocabulary) = self.vocabularies.get_mut(vocabulary) {
+            vocabulary.words.remove(name);
+            vocabulary.private.remove(name);
+        }
+    }
+
+    pub fn is_builtin(&self, name: &str) -> bool {
+        self.vocabularies[CORE].words.contains_key(name)
+    }
+
+    // Find the word a name refers to: `vocabulary:word`, or the first match in
+    // the current vocabulary and then the search order
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        if let Some((name_of, word)) = name.split_once(':')
+            && let Some(vocabulary) = self.vocabularies.get(name_of)
+        {
+            return self.visible(name_of, vocabulary, word);
+        }
+
+        let current = std::iter::once(&self.current);
+        current
+            .chain(self.search_order.iter().rev())
+            .find_map(|name_of| {
+                let vocabulary = &self.vocabularies[name_of];
+                self.visible(name_of, vocabulary, name)
+            })
+    }
+
+    fn visible(&self, name_of: &str, vocabulary: &Vocabulary, word: &str) -> Option<usize> {
+        let index = *vocabulary.words.get(word)?;
+        if vocabulary.private.contains(word) && name_of != self.current {
+            return None;
+        }
+        Some(index)
+    }
+}
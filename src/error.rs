@@ -0,0 +1,211 @@
+use crate::tokenizer::{Span, Token};
+use crate::types::Type;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    StackUnderflow,
+    TypeMismatch { expected: String, got: String },
+    UnknownWord(String),
+    UndefinedVariable(String),
+    DivisionByZero,
+    // Only in strict mode, otherwise ints grow as needed
+    IntegerOverflow,
+    IndexOutOfRange { index: i64, len: usize },
+    MissingKey(String),
+    // Calls nested deeper than the interpreter allows
+    DepthExceeded(usize),
+    // A resource limit ran out, try can't catch it
+    LimitExceeded(String),
+    // Files that can't be found, read or included
+    Io(String),
+    // Malformed definitions and control structures
+    Syntax(String),
+    // Raised by host functions
+    Custom(String),
+    // A value raised with `throw`
+    Thrown(Type),
+}
+
+// A word call the error passed through on its way out
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub word: String,
+    pub span: Span,
+    pub file: Option<Rc<Path>>,
+    // Tail calls between this call and the one before it, which left no frame
+    pub omitted: usize,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' at ", self.word)?;
+        if let Some(file) = &self.file {
+            write!(f, "{}, ", file.display())?;
+        }
+        write!(f, "{}", self.span)
+    }
+}
+
+// Boxed so results stay small on the hot path
+#[derive(Debug, Clone)]
+pub struct Error(Box<ErrorInner>);
+
+#[derive(Debug, Clone)]
+struct ErrorInner {
+    kind: ErrorKind,
+    // The word that was executing when the error happened
+    word: Option<String>,
+    span: Option<Span>,
+    file: Option<Rc<Path>>,
+    // The text the span points into
+    source: Option<Rc<str>>,
+    // Innermost call first
+    backtrace: Vec<Frame>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Error(Box::new(ErrorInner {
+            kind,
+            word: None,
+            span: None,
+            file: None,
+            source: None,
+            backtrace: Vec::new(),
+        }))
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.0.kind
+    }
+
+    pub fn word(&self) -> Option<&str> {
+        self.0.word.as_deref()
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.0.span
+    }
+
+    // The file the error happened in, None for source passed to `eval`
+    pub fn file(&self) -> Option<&Path> {
+        self.0.file.as_deref()
+    }
+
+    // The whole source the error happened in, as it was when it was read
+    pub fn source(&self) -> Option<&str> {
+        self.0.source.as_deref()
+    }
+
+    pub fn backtrace(&self) -> &[Frame] {
+        &self.0.backtrace
+    }
+
+    // What a `catch` gets: the thrown value, or the error message
+    pub fn value(&self) -> Type {
+        match self.kind() {
+            ErrorKind::Thrown(value) => value.clone(),
+            kind => Type::String(kind.to_string()),
+        }
+    }
+
+    pub fn type_mismatch(expected: &str, got: &Type) -> Self {
+        Error::new(ErrorKind::TypeMismatch {
+            expected: expected.to_string(),
+            got: got.type_name().to_string(),
+        })
+    }
+
+    pub fn syntax(message: impl Into<String>) -> Self {
+        Error::new(ErrorKind::Syntax(message.into()))
+    }
+
+    pub fn custom(message: impl Into<String>) -> Self {
+        Error::new(ErrorKind::Custom(message.into()))
+    }
+
+    // Record where the error happened, or if that is already known,
+    // that it passed through the call at `token`
+    pub fn at(mut self, token: &Token) -> Self {
+        let inner = &mut self.0;
+        if inner.span.is_none() {
+            inner.word = Some(token.text.clone());
+            inner.span = Some(token.span);
+            inner.file = token.file.clone();
+            inner.source = Some(token.source.clone());
+        } else {
+            inner.backtrace.push(Frame {
+                word: token.text.clone(),
+                span: token.span,
+                file: token.file.clone(),
+                omitted: 0,
+            });
+        }
+        self
+    }
+
+    // Note that tail calls were made, and replaced, since the last recorded call
+    pub(crate) fn omitted(mut self, calls: usize) -> Self {
+        if let Some(frame) = self.0.backtrace.last_mut() {
+            frame.omitted += calls;
+        }
+        self
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error::new(kind)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::StackUnderflow => write!(f, "Stack underflow"),
+            ErrorKind::TypeMismatch { expected, got } => {
+                write!(f, "Expected {}, got {}", expected, got)
+            }
+            ErrorKind::UnknownWord(word) => write!(f, "Unknown word '{}'", word),
+            ErrorKind::UndefinedVariable(name) => write!(f, "Variable '{}' not found", name),
+            ErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            ErrorKind::IntegerOverflow => write!(f, "Integer overflow"),
+            ErrorKind::MissingKey(key) => write!(f, "Key \"{}\" not found", key),
+            ErrorKind::DepthExceeded(max) => {
+                write!(f, "Recursion depth exceeded ({} calls)", max)
+            }
+            ErrorKind::IndexOutOfRange { index, len } => {
+                write!(f, "Index {} out of range for length {}", index, len)
+            }
+            ErrorKind::Syntax(message)
+            | ErrorKind::Io(message)
+            | ErrorKind::LimitExceeded(message)
+            | ErrorKind::Custom(message) => write!(f, "{}", message),
+            ErrorKind::Thrown(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind())?;
+        if let Some(word) = self.word() {
+            write!(f, " in '{}'", word)?;
+        }
+        if let Some(span) = self.span() {
+            write!(f, " at ")?;
+            if let Some(file) = self.file() {
+                write!(f, "{}, ", file.display())?;
+            }
+            write!(f, "{}", span)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
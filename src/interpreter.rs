@@ -1,52 +1,377 @@
-use crate::tokenizer::tokenize;
+use crate::compiler::{self, CLOSERS, CONTROL_WORDS, Code, DIRECTIVES, OPENERS, Op};
+use crate::decimal::Rounding;
+use crate::dictionary::{CORE, Dictionary, USER};
+use crate::error::{Error, ErrorKind, Result};
+use crate::limits::{Budget, Limits};
+use crate::number::{self, Arith, Division, NumberConfig};
+use crate::random::Rng;
+use crate::tokenizer::{Token, TokenKind, tokenize};
 use crate::types::Type;
 use crate::words;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub type NativeFn = Rc<dyn Fn(&mut Interpreter) -> Result<()>>;
 
 #[derive(Clone)]
 pub enum Word {
-    Native(fn(&mut Interpreter) -> Result<(), String>),
-    UserDefined(Vec<String>),
+    Native(NativeFn),
+    UserDefined(Rc<Code>),
+}
+
+// What a `]` closes
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Literal {
+    List,
+    Map,
+}
+
+// Calls deeper than this fail instead of using up memory
+const DEFAULT_MAX_DEPTH: usize = 10_000;
+
+// Code run by native words like call, each or try recurses on the Rust
+// stack, so it can only nest this deep
+const MAX_NESTING: usize = 1000;
+
+// Where a leave inside a try block moves the pc, ending the block
+const LEFT_LOOP: usize = usize::MAX;
+
+// State of one running word
+struct CallFrame {
+    code: Rc<Code>,
+    // Next instruction
+    pc: usize,
+    // Active do loops as (index, limit)
+    loops: Vec<(i64, i64)>,
+    // Values of the word's locals, by slot
+    locals: Vec<Type>,
+    // Part of a word, like a try block, that has to return to it
+    block: bool,
+    // Tail calls that replaced the frame, shown in backtraces as a count
+    tail_calls: usize,
+}
+
+// Whether nothing but jumps follow `pc`, so a call there can replace its frame
+fn is_tail(code: &Code, mut pc: usize) -> bool {
+    loop {
+        match code.ops.get(pc) {
+            None => return true,
+            Some(Op::Jump(target)) => pc = *target,
+            Some(_) => return false,
+        }
+    }
+}
+
+// Interpreter state saved by `snapshot` and put back by `restore`
+#[derive(Clone)]
+pub struct Snapshot {
+    stack: Vec<Type>,
+    dictionary: Dictionary,
+    variables: HashMap<String, Type>,
+    literal_marks: Vec<(usize, Literal)>,
+    loaded: HashSet<PathBuf>,
+    compiling: bool,
+    current_word_name: String,
+    current_definition: Vec<Token>,
 }
 
 pub struct Interpreter {
     pub stack: Vec<Type>,
-    pub dictionary: HashMap<String, Word>,
+    // Every word ever defined, never removed; compiled code calls them by index
+    pub(crate) words: Vec<Word>,
+    // Name -> index into `words` of the latest visible definition
+    pub(crate) dictionary: Dictionary,
     pub variables: HashMap<String, Type>,
+    // Stack depths where unfinished `[ ... ]` and `#[ ... ]` literals started
+    pub(crate) literal_marks: Vec<(usize, Literal)>,
+
+    // Directories searched by include/require after the including file's own
+    search_path: Vec<PathBuf>,
+    // Files being evaluated as (canonical path, path as found), innermost last
+    including: Vec<(PathBuf, Rc<Path>)>,
+    // Canonical paths of every file evaluated so far, for require
+    loaded: HashSet<PathBuf>,
 
     compiling: bool,
     current_word_name: String,
-    current_definition: Vec<String>,
+    current_definition: Vec<Token>,
+
+    // Whether a failed eval restores the state from before it
+    transactional: bool,
+
+    // Frames of the words waiting for a call to return, innermost last
+    frames: Vec<CallFrame>,
+    max_depth: usize,
+    // Number of run_frame calls in progress
+    nesting: usize,
+
+    budget: Budget,
+    // Lowest stack depth since the last budget check, the values above it
+    // may have changed
+    stack_low: usize,
+    pub(crate) numbers: NumberConfig,
+    // Source of the random words, seeded from the clock unless set_seed is used
+    pub(crate) rng: Rng,
+    // Where words like `.` write to
+    output: Box<dyn Write>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         let mut interp = Interpreter {
             stack: Vec::new(),
-            dictionary: HashMap::new(),
+            words: Vec::new(),
+            dictionary: Dictionary::new(),
             variables: HashMap::new(),
+            literal_marks: Vec::new(),
+            search_path: Vec::new(),
+            including: Vec::new(),
+            loaded: HashSet::new(),
             compiling: false,
             current_word_name: String::new(),
             current_definition: Vec::new(),
+            transactional: false,
+            frames: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            nesting: 0,
+            budget: Budget::default(),
+            stack_low: 0,
+            numbers: NumberConfig::default(),
+            rng: Rng::from_time(),
+            output: Box::new(io::stdout()),
         };
 
         words::register_math_words(&mut interp);
+        words::register_bitwise_words(&mut interp);
+        words::register_stats_words(&mut interp);
+        words::register_random_words(&mut interp);
         words::register_stack_words(&mut interp);
         words::register_comparison_words(&mut interp);
         words::register_logic_words(&mut interp);
         words::register_io_words(&mut interp);
         words::register_string_words(&mut interp);
+        words::register_list_words(&mut interp);
+        words::register_map_words(&mut interp);
+        words::register_combinator_words(&mut interp);
+        words::register_exception_words(&mut interp);
+        words::register_file_words(&mut interp);
+
+        // `.` prints ints in this radix
+        interp.set_var("base", 10);
 
+        // Everything defined from here on goes into the user vocabulary
+        interp.dictionary.set_current(USER);
+
+        interp
+    }
+
+    // An interpreter for untrusted scripts: sandbox limits and no words
+    // that touch files
+    pub fn sandboxed() -> Self {
+        let mut interp = Interpreter::new();
+        for name in words::FILE_WORDS {
+            interp.dictionary.remove(CORE, name);
+        }
+        interp.set_limits(Limits::sandbox());
         interp
     }
 
+    // Limit what each eval and eval_file may use
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.budget = Budget::new(limits);
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.budget.limits
+    }
+
+    // In strict mode integer overflow is an error instead of giving a big integer
+    pub fn set_strict_integers(&mut self, strict: bool) {
+        self.numbers.strict = strict;
+    }
+
+    // Which way integer division and mod round, truncating by default
+    pub fn set_division(&mut self, division: Division) {
+        self.numbers.division = division;
+    }
+
+    // Most digits after the point decimal results keep, 28 by default and at
+    // most MAX_DECIMAL_SCALE
+    pub fn set_decimal_scale(&mut self, scale: u32) -> Result<()> {
+        self.numbers.decimal_scale = number::decimal_scale(scale.into())?;
+        Ok(())
+    }
+
+    // How decimal results are rounded to that scale, half-even by default
+    pub fn set_rounding(&mut self, rounding: Rounding) {
+        self.numbers.rounding = rounding;
+    }
+
+    // Read literals like 0.1 as decimals instead of floats
+    pub fn set_decimal_literals(&mut self, decimal: bool) {
+        self.numbers.decimal_literals = decimal;
+    }
+
+    // Make the random words give the same numbers on every run
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    // Send output of words like `.` somewhere else than stdout
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.output = Box::new(output);
+    }
+
+    // Write program output, counting it against the output limit
+    pub fn write_output(&mut self, text: &str) -> Result<()> {
+        self.budget.output(text.len())?;
+        self.output
+            .write_all(text.as_bytes())
+            .map_err(|e| Error::new(ErrorKind::Io(format!("Failed to write output: {}", e))))
+    }
+
+    // Add a word to the dictionary. Redefining a name creates a new word,
+    // words compiled earlier keep calling the definition they were compiled with.
+    pub(crate) fn define(&mut self, name: &str, word: Word) {
+        self.dictionary.insert(name, self.words.len());
+        self.words.push(word);
+    }
+
+    // Register a host function as a word, it can capture whatever it needs
+    pub fn register<F>(&mut self, name: &str, func: F)
+    where
+        F: Fn(&mut Interpreter) -> Result<()> + 'static,
+    {
+        self.define(name, Word::Native(Rc::new(func)));
+    }
+
+    // Register a host function that mutates its own state, like a counter or a
+    // connection handle. It must not end up calling itself while running.
+    pub fn register_mut<F>(&mut self, name: &str, func: F)
+    where
+        F: FnMut(&mut Interpreter) -> Result<()> + 'static,
+    {
+        let word = name.to_string();
+        let func = RefCell::new(func);
+        self.register(name, move |interp| {
+            let mut func = func
+                .try_borrow_mut()
+                .map_err(|_| Error::custom(format!("'{}' cannot call itself", word)))?;
+            func(interp)
+        });
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.dictionary.lookup(name).is_some()
+    }
+
+    pub fn push(&mut self, value: impl Into<Type>) {
+        self.stack.push(value.into());
+    }
+
+    pub fn peek(&self) -> Option<&Type> {
+        self.stack.last()
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<&Type> {
+        self.variables.get(name)
+    }
+
+    pub fn set_var(&mut self, name: &str, value: impl Into<Type>) {
+        let value = value.into();
+        if self.budget.active {
+            self.budget.store(name, self.variables.get(name), &value);
+        }
+        self.variables.insert(name.to_string(), value);
+    }
+
     // Helper functions
-    pub fn pop_number(&mut self) -> Result<(f64, bool), String> {
-        match self.stack.pop() {
-            Some(Type::Int(n)) => Ok((n as f64, true)), // true = was int
-            Some(Type::Float(f)) => Ok((f, false)),     // false = was float
-            Some(other) => Err(format!("Expected number, got {:?}", other)),
-            None => Err("Stack is empty!".to_string()),
+    pub fn pop(&mut self) -> Result<Type> {
+        let value = self
+            .stack
+            .pop()
+            .ok_or_else(|| ErrorKind::StackUnderflow.into());
+        self.stack_low = self.stack_low.min(self.stack.len());
+        value
+    }
+
+    // Take everything from `at` up off the stack
+    pub(crate) fn split_stack(&mut self, at: usize) -> Vec<Type> {
+        self.stack_low = self.stack_low.min(at);
+        self.stack.split_off(at)
+    }
+
+    // Fail up front when work about to be done would need more memory than
+    // the limit leaves
+    pub fn reserve(&self, bytes: usize) -> Result<()> {
+        self.budget.reserve(bytes)
+    }
+
+    pub fn pop_float(&mut self) -> Result<f64> {
+        self.pop_number().map(|(n, _)| n)
+    }
+
+    pub fn pop_number(&mut self) -> Result<(f64, bool)> {
+        match self.pop()? {
+            Type::Int(n) => Ok((n as f64, true)), // true = was int
+            Type::BigInt(n) => Ok((n.to_f64(), true)),
+            Type::Rational(r) => Ok((r.to_f64(), false)),
+            Type::Decimal(d) => Ok((d.to_f64(), false)),
+            Type::Float(f) => Ok((f, false)), // false = was float
+            other => Err(Error::type_mismatch("number", &other)),
+        }
+    }
+
+    pub fn pop_int(&mut self) -> Result<i64> {
+        match self.pop()? {
+            Type::Int(n) => Ok(n),
+            Type::BigInt(n) => Err(Error::custom(format!("{} does not fit in 64 bits", n))),
+            other => Err(Error::type_mismatch("int", &other)),
+        }
+    }
+
+    pub fn pop_bool(&mut self) -> Result<bool> {
+        match self.pop()? {
+            Type::Bool(b) => Ok(b),
+            other => Err(Error::type_mismatch("bool", &other)),
+        }
+    }
+
+    pub fn pop_list(&mut self) -> Result<Vec<Type>> {
+        match self.pop()? {
+            Type::List(items) => Ok(items),
+            other => Err(Error::type_mismatch("list", &other)),
+        }
+    }
+
+    pub fn pop_map(&mut self) -> Result<BTreeMap<String, Type>> {
+        match self.pop()? {
+            Type::Map(map) => Ok(map),
+            other => Err(Error::type_mismatch("map", &other)),
+        }
+    }
+
+    pub fn pop_quotation(&mut self) -> Result<Rc<Code>> {
+        match self.pop()? {
+            Type::Quotation(code) => Ok(code),
+            other => Err(Error::type_mismatch("quotation", &other)),
+        }
+    }
+
+    pub fn pop_string(&mut self) -> Result<String> {
+        match self.pop()? {
+            Type::String(s) => Ok(s),
+            other => Err(Error::type_mismatch("string", &other)),
         }
     }
 
@@ -54,36 +379,209 @@ impl Interpreter {
     // 3 + 3 = 6
     // 3 + 3.0 = 6
     // 3 + 3.1 = 6.1
-    // This checks for floats or ints and is used for mixed operations
-    pub fn binary_op<F>(&mut self, op: F) -> Result<(), String>
-    where
-        F: Fn(f64, f64) -> f64,
-    {
+    // Ints stay exact and grow into big integers, anything with a float gives a float
+    pub fn binary_op(&mut self, op: Arith) -> Result<()> {
         // tos : Top of Stack
         // nos : Next on Stack
-        let (tos, tos_is_int) = self.pop_number()?;
-        let (nos, second_is_int) = self.pop_number()?;
+        let tos = self.pop()?;
+        let nos = self.pop()?;
 
-        if second_is_int && tos_is_int {
-            self.stack.push(Type::Int((op(nos, tos)) as i64));
-        } else {
-            self.stack.push(Type::Float(op(nos, tos)));
-        }
+        let result = number::arith(op, &nos, &tos, &self.numbers)?;
+        self.stack.push(result);
         Ok(())
     }
 
+    // Save everything an eval can change, except the output it printed
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            stack: self.stack.clone(),
+            dictionary: self.dictionary.clone(),
+            variables: self.variables.clone(),
+            literal_marks: self.literal_marks.clone(),
+            loaded: self.loaded.clone(),
+            compiling: self.compiling,
+            current_word_name: self.current_word_name.clone(),
+            current_definition: self.current_definition.clone(),
+        }
+    }
+
+    // Go back to the state of a snapshot, words defined since are forgotten.
+    // Their code stays in `words`, quotations handed out since may still call
+    // it and newer snapshots may still name it.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.stack = snapshot.stack;
+        self.dictionary = snapshot.dictionary;
+        self.variables = snapshot.variables;
+        self.literal_marks = snapshot.literal_marks;
+        self.loaded = snapshot.loaded;
+        self.compiling = snapshot.compiling;
+        self.current_word_name = snapshot.current_word_name;
+        self.current_definition = snapshot.current_definition;
+        self.budget.recount(&self.stack, &self.variables);
+        self.stack_low = self.stack.len();
+    }
+
+    // In transactional mode a failed eval or eval_file leaves the interpreter
+    // as it was before the call
+    pub fn set_transactional(&mut self, transactional: bool) {
+        self.transactional = transactional;
+    }
+
+    // How deep words may call each other, tail calls don't count
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    fn transaction(&mut self, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        // Limits count from the outermost eval, not from files it includes
+        if self.nesting == 0 {
+            self.budget.reset(&self.stack, &self.variables);
+            self.stack_low = self.stack.len();
+        }
+        if !self.transactional {
+            return f(self);
+        }
+        let snapshot = self.snapshot();
+        let result = f(self);
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+        result
+    }
+
     // Evaluate the tokens
-    pub fn eval(&mut self, input: &str) -> Result<(), String> {
-        let tokens = tokenize(input);
+    pub fn eval(&mut self, input: &str) -> Result<()> {
+        self.transaction(|interp| interp.eval_source(input, None))
+    }
+
+    fn eval_source(&mut self, input: &str, file: Option<Rc<Path>>) -> Result<()> {
+        let result = self.eval_tokens(tokenize(input, file));
+        if result.is_err() {
+            // Unfinished literals can't be completed anymore
+            self.literal_marks.clear();
+        }
+        result
+    }
 
-        for token in tokens {
+    // Evaluate a whole file. Files it includes are looked up relative to it.
+    pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        self.transaction(|interp| interp.eval_path(path))
+    }
+
+    fn eval_path(&mut self, path: &Path) -> Result<()> {
+        let io_error = |e: std::io::Error| {
+            Error::new(ErrorKind::Io(format!(
+                "Failed to read file '{}': {}",
+                path.display(),
+                e
+            )))
+        };
+        let canonical = path.canonicalize().map_err(io_error)?;
+
+        if let Some(start) = self.including.iter().position(|(p, _)| *p == canonical) {
+            let mut chain: Vec<String> = self.including[start..]
+                .iter()
+                .map(|(_, name)| name.display().to_string())
+                .collect();
+            chain.push(path.display().to_string());
+            let message = format!("Circular include: {}", chain.join(" -> "));
+            return Err(Error::new(ErrorKind::Io(message)));
+        }
+
+        let contents = fs::read_to_string(&canonical).map_err(io_error)?;
+
+        let marks = self.literal_marks.len();
+        let file: Rc<Path> = Rc::from(path);
+        self.including.push((canonical.clone(), file.clone()));
+        let result = self.eval_source(&contents, Some(file));
+        self.including.pop();
+        result?;
+
+        // A definition can't continue past the end of its file
+        if self.compiling {
+            self.compiling = false;
+            self.current_definition.clear();
+            let name = std::mem::take(&mut self.current_word_name);
+            let message = format!(
+                "Unterminated definition of '{}' in {}",
+                name,
+                path.display()
+            );
+            return Err(Error::syntax(message));
+        }
+        // Nor can a list or map literal
+        if let Some(&(_, literal)) = self.literal_marks.get(marks) {
+            self.literal_marks.truncate(marks);
+            let open = match literal {
+                Literal::List => "[",
+                Literal::Map => "#[",
+            };
+            let message = format!("Unterminated '{}' in {}", open, path.display());
+            return Err(Error::syntax(message));
+        }
+
+        // Only now, so a require that failed is tried again
+        self.loaded.insert(canonical);
+        Ok(())
+    }
+
+    // Like eval_file, but does nothing if the file was already evaluated
+    pub fn require_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        // Files still being evaluated count too, so files can require each other
+        if let Ok(canonical) = path.canonicalize()
+            && (self.loaded.contains(&canonical)
+                || self.including.iter().any(|(p, _)| *p == canonical))
+        {
+            return Ok(());
+        }
+        self.eval_file(path)
+    }
+
+    // Add a directory to search for included files
+    pub fn add_search_path(&mut self, dir: impl Into<PathBuf>) {
+        self.search_path.push(dir.into());
+    }
+
+    // Find a file named by include/require: relative to the including file
+    // (or the working directory), then in the search path
+    pub(crate) fn resolve_include(&self, name: &str) -> Result<PathBuf> {
+        let path = Path::new(name);
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+
+        let base = match self.including.last() {
+            Some((_, file)) => file.parent().unwrap_or(Path::new("")).to_path_buf(),
+            None => PathBuf::new(),
+        };
+        std::iter::once(base)
+            .chain(self.search_path.iter().cloned())
+            .map(|dir| dir.join(path))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| Error::new(ErrorKind::Io(format!("Cannot find file '{}'", name))))
+    }
+
+    fn eval_tokens(&mut self, tokens: Vec<Token>) -> Result<()> {
+        // Top-level control structures are collected until they are closed
+        // and then executed as a whole
+        let mut pending: Vec<Token> = Vec::new();
+        let mut depth = 0;
+
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
             // Debug the Stack and Token Evaluation
-            //println!("DEBUG: Processing token '{}'", token);
+            //println!("DEBUG: Processing token '{:?}'", token);
 
             // Handle word definition start
-            if token == ":" {
+            if token.is(":") {
                 if self.compiling {
-                    return Err("Already defining a word".to_string());
+                    return Err(Error::syntax("Already defining a word").at(&token));
+                }
+                if depth > 0 {
+                    let message = "Cannot define a word inside a control structure";
+                    return Err(Error::syntax(message).at(&token));
                 }
                 self.compiling = true;
                 self.current_definition.clear();
@@ -92,21 +590,22 @@ impl Interpreter {
             }
 
             // Handle word definition end
-            if token == ";" {
+            if token.is(";") {
                 if !self.compiling {
-                    return Err("Not defining a word".to_string());
+                    return Err(Error::syntax("Not defining a word").at(&token));
                 }
                 if self.current_word_name.is_empty() {
-                    return Err("No word name specified".to_string());
+                    return Err(Error::syntax("No word name specified").at(&token));
                 }
 
                 let name = self.current_word_name.clone();
-                let def = self.current_definition.clone();
-                self.dictionary.insert(name, Word::UserDefined(def));
-
+                let def = std::mem::take(&mut self.current_definition);
                 self.compiling = false;
                 self.current_word_name.clear();
-                self.current_definition.clear();
+
+                // The word may call itself under the index it is about to get
+                let code = compiler::compile(self, &def, Some((&name, self.words.len())))?;
+                self.define(&name, Word::UserDefined(Rc::new(code)));
                 continue;
             }
 
@@ -114,81 +613,419 @@ impl Interpreter {
             if self.compiling {
                 if self.current_word_name.is_empty() {
                     // First token after : is the word name
-                    self.current_word_name = token.clone();
+                    if let Err(e) = self.check_word_name(&token) {
+                        self.compiling = false;
+                        return Err(e.at(&token));
+                    }
+                    self.current_word_name = token.text;
                 } else {
                     // Rest are the definition
-                    self.current_definition.push(token.clone());
+                    self.current_definition.push(token);
+                }
+                continue;
+            }
+
+            // Vocabulary handling, outside of everything else
+            if token.kind == TokenKind::Word && DIRECTIVES.contains(&token.text.as_str()) {
+                if depth > 0 {
+                    let message = format!("Cannot use '{}' inside a control structure", token.text);
+                    return Err(Error::syntax(message).at(&token));
                 }
+                self.directive(&token, &mut tokens)?;
                 continue;
             }
 
-            // Not compiling - execute the token normally
-            self.eval_token(&token)?;
+            // Not compiling - track control structures at the top level
+            let control = |words: &[&str]| {
+                token.kind == TokenKind::Word && words.contains(&token.text.as_str())
+            };
+            if control(OPENERS) {
+                depth += 1;
+            } else if control(CLOSERS) && depth > 0 {
+                depth -= 1;
+            }
+
+            if depth > 0 || control(CONTROL_WORDS) {
+                pending.push(token);
+                if depth == 0 {
+                    let block = std::mem::take(&mut pending);
+                    self.execute(&block)?;
+                }
+                continue;
+            }
+
+            // Execute the token normally
+            self.execute(&[token])?;
+        }
+
+        // Reports the unterminated structure
+        if !pending.is_empty() {
+            self.execute(&pending)?;
         }
 
         Ok(())
     }
 
-    // Helper method to evaluate a single token
-    fn eval_token(&mut self, token: &str) -> Result<(), String> {
-        // Check for string literal
-        if token.starts_with("STR:") {
-            let s = &token[4..];
-            self.stack.push(Type::String(s.to_string()));
-            return Ok(());
+    fn check_word_name(&self, token: &Token) -> Result<()> {
+        if token.kind == TokenKind::Str {
+            return Err(Error::syntax("Word name cannot be a string"));
+        }
+        if token.text.contains(':') {
+            return Err(Error::syntax("Word name cannot contain ':'"));
         }
+        if self.dictionary.is_builtin(&token.text) {
+            let message = format!("Cannot redefine builtin word '{}'", token.text);
+            return Err(Error::syntax(message));
+        }
+        Ok(())
+    }
 
-        // Variable storage
-        if token.ends_with("!") && token.len() > 1 {
-            let var_name = &token[..token.len() - 1];
-            let value = self
-                .stack
-                .pop()
-                .ok_or("Stack underflow! Need a value to store")?;
-            self.variables.insert(var_name.to_string(), value);
-            return Ok(());
+    // Run a vocabulary directive, taking the vocabulary name from the tokens that follow
+    fn directive(&mut self, token: &Token, rest: &mut impl Iterator<Item = Token>) -> Result<()> {
+        let mut name = || {
+            let message = format!("'{}' needs a vocabulary name", token.text);
+            match rest.next() {
+                Some(name) if name.kind == TokenKind::Word => Ok(name.text),
+                _ => Err(Error::syntax(message).at(token)),
+            }
+        };
+
+        match token.text.as_str() {
+            "vocabulary" => self.dictionary.create(&name()?),
+            "in" | "also" => {
+                let vocabulary = name()?;
+                if !self.dictionary.exists(&vocabulary) {
+                    let message = format!("Unknown vocabulary '{}'", vocabulary);
+                    return Err(Error::syntax(message).at(token));
+                }
+                if token.is("in") {
+                    self.dictionary.set_current(&vocabulary);
+                } else {
+                    self.dictionary.also(&vocabulary);
+                }
+            }
+            "only" => self.dictionary.only(),
+            "private" => self.dictionary.set_private(true),
+            "public" => self.dictionary.set_private(false),
+            _ => unreachable!(),
         }
+        Ok(())
+    }
 
-        // Variable retrieval
-        if token.ends_with("@") && token.len() > 1 {
-            let var_name = &token[..token.len() - 1];
-            let value = self
-                .variables
-                .get(var_name)
-                .ok_or_else(|| format!("Variable '{}' not found", var_name))?
-                .clone();
-            self.stack.push(value);
-            return Ok(());
+    // Compile and run top-level tokens
+    fn execute(&mut self, tokens: &[Token]) -> Result<()> {
+        let code = compiler::compile(self, tokens, None)?;
+        self.run(&Rc::new(code))
+    }
+
+    // Call a quotation value on the current stack
+    pub fn call_quotation(&mut self, quotation: &Type) -> Result<()> {
+        match quotation {
+            Type::Quotation(code) => self.run(code),
+            other => Err(Error::type_mismatch("quotation", other)),
         }
+    }
 
-        // Floats
-        if token.contains('.') && token.parse::<f64>().is_ok() {
-            let f = token.parse::<f64>().unwrap();
-            self.stack.push(Type::Float(f));
-            return Ok(());
+    // Run compiled code
+    pub(crate) fn run(&mut self, code: &Rc<Code>) -> Result<()> {
+        let mut frame = self.enter(code.clone())?;
+        self.run_frame(&mut frame)
+    }
+
+    // A frame for running code, taking its locals off the stack
+    fn enter(&mut self, code: Rc<Code>) -> Result<CallFrame> {
+        let mut locals = Vec::new();
+        if code.locals > 0 {
+            // The first local gets the deepest argument
+            let Some(start) = self.stack.len().checked_sub(code.locals) else {
+                return Err(ErrorKind::StackUnderflow.into());
+            };
+            locals = self.split_stack(start);
+        }
+        Ok(CallFrame {
+            code,
+            pc: 0,
+            loops: Vec::new(),
+            locals,
+            block: false,
+            tail_calls: 0,
+        })
+    }
+
+    // Run a frame until it returns. Words it calls get frames on the
+    // return stack instead of recursing.
+    fn run_frame(&mut self, frame: &mut CallFrame) -> Result<()> {
+        if self.nesting >= MAX_NESTING {
+            return Err(ErrorKind::DepthExceeded(MAX_NESTING).into());
+        }
+        // Entering counts as an instruction, so code without any, like an
+        // empty quotation run by `times`, still uses up the limits
+        self.charge()?;
+        self.nesting += 1;
+        let result = self.run_frames(frame);
+        self.nesting -= 1;
+        result
+    }
+
+    fn run_frames(&mut self, frame: &mut CallFrame) -> Result<()> {
+        let base = self.frames.len();
+
+        loop {
+            let CallFrame {
+                code,
+                pc,
+                loops,
+                locals,
+                ..
+            } = frame;
+            let Some(op) = code.ops.get(*pc) else {
+                // Return to the caller
+                if self.frames.len() == base {
+                    return Ok(());
+                }
+                *frame = self.frames.pop().unwrap();
+                continue;
+            };
+            *pc += 1;
+            let next = *pc;
+
+            let callee = self
+                .step(op, pc, loops, locals)
+                .and_then(|callee| self.charge().map(|()| callee));
+            let callee = match callee {
+                Ok(None) => continue,
+                Ok(Some(callee)) => self.enter(callee),
+                Err(e) => Err(e),
+            };
+            let callee = match callee {
+                Ok(callee) => callee,
+                Err(e) => {
+                    // A jump may have moved on already, report the instruction itself
+                    frame.pc = next;
+                    return Err(self.unwind(frame, base, e));
+                }
+            };
+
+            // The frame that started running stays, so a backtrace always
+            // shows where in the caller's code the calls began
+            if !frame.block && self.frames.len() > base && is_tail(&frame.code, frame.pc) {
+                // Nothing left to do in this frame, reuse its place
+                let tail_calls = frame.tail_calls + 1;
+                *frame = callee;
+                frame.tail_calls = tail_calls;
+            } else if self.frames.len() >= self.max_depth {
+                let e = ErrorKind::DepthExceeded(self.max_depth).into();
+                return Err(self.unwind(frame, base, e));
+            } else {
+                self.frames.push(std::mem::replace(frame, callee));
+            }
         }
+    }
 
-        // Integers
-        if let Ok(n) = token.parse::<i64>() {
-            self.stack.push(Type::Int(n));
+    // Count an instruction against the limits
+    fn charge(&mut self) -> Result<()> {
+        if !self.budget.active {
             return Ok(());
         }
+        let low = std::mem::replace(&mut self.stack_low, self.stack.len());
+        self.budget.check(&self.stack, low)
+    }
 
-        // Dictionary lookup
-        if let Some(word) = self.dictionary.get(token).cloned() {
-            match word {
-                Word::Native(func) => return func(self),
-                Word::UserDefined(tokens) => {
-                    // Execute each token in the definition
-                    for t in tokens {
-                        self.eval_token(&t)?;
+    // Drop the frames above `base`, recording each call in the error
+    fn unwind(&mut self, frame: &mut CallFrame, base: usize, mut e: Error) -> Error {
+        let mut omitted = 0;
+        loop {
+            e = e.at(&frame.code.tokens[frame.pc - 1]).omitted(omitted);
+            omitted = frame.tail_calls;
+            if self.frames.len() <= base {
+                return e;
+            }
+            *frame = self.frames.pop().unwrap();
+        }
+    }
+
+    // Execute a single instruction, returning the user defined word to call if any
+    fn step(
+        &mut self,
+        op: &Op,
+        pc: &mut usize,
+        loops: &mut Vec<(i64, i64)>,
+        locals: &mut Vec<Type>,
+    ) -> Result<Option<Rc<Code>>> {
+        match op {
+            Op::Push(value) => self.stack.push(value.clone()),
+            Op::Native(func) => func(self)?,
+            Op::Call(index) => return self.call(*index),
+            Op::CallName(name) => {
+                let index = self
+                    .dictionary
+                    .lookup(name)
+                    .ok_or_else(|| ErrorKind::UnknownWord(name.clone()))?;
+                return self.call(index);
+            }
+            Op::Store(name) => {
+                let value = self.pop()?;
+                if self.budget.active {
+                    self.budget.store(name, self.variables.get(name), &value);
+                }
+                // Avoid allocating a new key when overwriting
+                match self.variables.get_mut(name) {
+                    Some(slot) => *slot = value,
+                    None => {
+                        self.variables.insert(name.clone(), value);
+                    }
+                }
+            }
+            Op::Load(name) => {
+                let value = self
+                    .variables
+                    .get(name)
+                    .ok_or_else(|| ErrorKind::UndefinedVariable(name.clone()))?
+                    .clone();
+                self.stack.push(value);
+            }
+            Op::LoadLocal(slot) => self.stack.push(locals[*slot].clone()),
+            Op::StoreLocal(slot) => locals[*slot] = self.pop()?,
+            Op::Jump(target) => *pc = *target,
+            Op::JumpIfFalse(target) => {
+                if !self.pop_bool()? {
+                    *pc = *target;
+                }
+            }
+            Op::Do | Op::QDo(_) => {
+                let start = self.pop_int()?;
+                let limit = self.pop_int()?;
+                // ?do skips the body entirely when there is nothing to count
+                if let Op::QDo(end) = op
+                    && start == limit
+                {
+                    *pc = *end;
+                    return Ok(None);
+                }
+                loops.push((start, limit));
+            }
+            Op::Loop(body) | Op::PlusLoop(body) => {
+                let step = if let Op::PlusLoop(_) = op {
+                    self.pop_int()?
+                } else {
+                    1
+                };
+                let (index, limit) = loops.last_mut().unwrap();
+                let old = *index;
+                *index = old.saturating_add(step);
+
+                // Like Forth, stop once the index crosses the boundary
+                // between limit - 1 and limit, in either direction
+                let done = if step >= 0 {
+                    old < *limit && *index >= *limit
+                } else {
+                    old >= *limit && *index < *limit
+                };
+                if done {
+                    loops.pop();
+                } else {
+                    *pc = *body;
+                }
+            }
+            Op::Leave(end) => {
+                loops.pop();
+                *pc = *end;
+            }
+            Op::LeaveBlock => *pc = LEFT_LOOP,
+            Op::I => {
+                let (index, _) = loops
+                    .last()
+                    .ok_or_else(|| Error::syntax("'i' used outside of a loop"))?;
+                self.stack.push(Type::Int(*index));
+            }
+            Op::J => {
+                let (index, _) = loops
+                    .iter()
+                    .rev()
+                    .nth(1)
+                    .ok_or_else(|| Error::syntax("'j' used outside of a nested loop"))?;
+                self.stack.push(Type::Int(*index));
+            }
+            Op::Try {
+                body,
+                handler,
+                cleanup,
+                leave,
+            } => {
+                let stack = self.stack.clone();
+                let marks = self.literal_marks.len();
+                let depth = loops.len();
+
+                let mut result = self.run_block(body, loops, locals);
+                if let (Err(e), Some(handler)) = (&result, handler)
+                    && !matches!(e.kind(), ErrorKind::LimitExceeded(_))
+                {
+                    // Back to the stack the body started with, plus the error
+                    let value = e.value();
+                    self.stack = stack;
+                    self.stack_low = 0;
+                    self.stack.push(value);
+                    self.literal_marks.truncate(marks);
+                    loops.truncate(depth);
+                    result = self.run_block(handler, loops, locals);
+                }
+
+                // Runs either way, an error it raises replaces the pending one
+                // and leaving the loop from it drops the pending one
+                if let Some(cleanup) = cleanup {
+                    loops.truncate(depth);
+                    if self.run_block(cleanup, loops, locals)? {
+                        result = Ok(true);
+                    }
+                }
+                if result? {
+                    match leave {
+                        Some(end) => {
+                            loops.pop();
+                            *pc = *end;
+                        }
+                        // The loop is around the block this try is in
+                        None => *pc = LEFT_LOOP,
                     }
-                    return Ok(());
                 }
             }
         }
 
-        Err(format!("Unknown token: {}", token))
+        Ok(None)
+    }
+
+    // Run part of a word, like a try block, with the loops and locals of the
+    // word. Returns whether a leave ended it.
+    fn run_block(
+        &mut self,
+        code: &Rc<Code>,
+        loops: &mut Vec<(i64, i64)>,
+        locals: &mut Vec<Type>,
+    ) -> Result<bool> {
+        let mut frame = CallFrame {
+            code: code.clone(),
+            pc: 0,
+            loops: std::mem::take(loops),
+            locals: std::mem::take(locals),
+            block: true,
+            tail_calls: 0,
+        };
+        let result = self.run_frame(&mut frame);
+        *loops = frame.loops;
+        *locals = frame.locals;
+        result.map(|()| frame.pc == LEFT_LOOP)
+    }
+
+    // Run a native word, or hand back the code of a user defined one
+    fn call(&mut self, index: usize) -> Result<Option<Rc<Code>>> {
+        match &self.words[index] {
+            Word::Native(func) => {
+                let func = func.clone();
+                func(self)?;
+                Ok(None)
+            }
+            Word::UserDefined(code) => Ok(Some(code.clone())),
+        }
     }
 
     // Show what's on the stack
@@ -198,12 +1035,7 @@ impl Interpreter {
             if i > 0 {
                 print!(", ");
             }
-            match val {
-                Type::Int(n) => print!("{}", n),
-                Type::String(s) => print!("\"{}\"", s),
-                Type::Float(f) => print!("{}", f),
-                Type::Bool(b) => print!("{}", b),
-            }
+            print!("{}", val.repr());
         }
         println!("]");
     }
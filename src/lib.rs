@@ -0,0 +1,58 @@
+//! Smorth, a small Forth-style stack language.
+//!
+//! The interpreter can be embedded and extended with host functions:
+//!
+//! ```
+//! use smorth::{Interpreter, Type};
+//!
+//! let mut interp = Interpreter::new();
+//! let greeting = String::from("Hello from the host");
+//! interp.register("greet", move |interp| {
+//!     interp.push(greeting.as_str());
+//!     Ok(())
+//! });
+//!
+//! interp.eval("greet \" World\" ..").unwrap();
+//! assert!(matches!(interp.pop(), Ok(Type::String(s)) if s == "Hello from the host World"));
+//! ```
+//!
+//! Words that keep their own mutable state use `register_mut`:
+//!
+//! ```
+//! use smorth::{Interpreter, Type};
+//!
+//! let mut interp = Interpreter::new();
+//! let mut calls = 0;
+//! interp.register_mut("next-id", move |interp| {
+//!     calls += 1;
+//!     interp.push(calls);
+//!     Ok(())
+//! });
+//!
+//! interp.eval("next-id drop next-id").unwrap();
+//! assert!(matches!(interp.pop(), Ok(Type::Int(2))));
+//! ```
+
+mod bigint;
+mod compiler;
+mod decimal;
+mod dictionary;
+mod error;
+mod interpreter;
+mod limits;
+mod number;
+mod random;
+mod rational;
+mod tokenizer;
+mod types;
+mod words;
+
+pub use bigint::BigInt;
+pub use decimal::{Decimal, Rounding};
+pub use error::{Error, ErrorKind, Frame, Result};
+pub use interpreter::{Interpreter, NativeFn, Snapshot};
+pub use limits::Limits;
+pub use number::{Arith, Division};
+pub use rational::Rational;
+pub use tokenizer::Span;
+pub use types::Type;
@@ -0,0 +1,178 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::types::Type;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Instructions between checks of the clock
+const CHECK_INTERVAL: u64 = 1024;
+// Bytes measured by one instruction that make it worth checking the clock
+// right away, so large values can't slow many instructions down unnoticed
+const LARGE_MEASURE: usize = 64 * 1024;
+
+// Resources a single eval or eval_file may use, None means unlimited
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    pub max_instructions: Option<u64>,
+    // Values on the stack
+    pub max_stack_depth: Option<usize>,
+    // Approximate bytes held by values on the stack and in variables
+    pub max_memory: Option<usize>,
+    pub max_time: Option<Duration>,
+    // Bytes written by words like `.`
+    pub max_output_bytes: Option<usize>,
+}
+
+impl Limits {
+    // Limits for running scripts nobody has looked at
+    pub fn sandbox() -> Self {
+        Limits {
+            max_instructions: Some(10_000_000),
+            max_stack_depth: Some(10_000),
+            max_memory: Some(64 * 1024 * 1024),
+            max_time: Some(Duration::from_secs(5)),
+            max_output_bytes: Some(1024 * 1024),
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.max_instructions.is_some()
+            || self.max_stack_depth.is_some()
+            || self.max_memory.is_some()
+            || self.max_time.is_some()
+            || self.max_output_bytes.is_some()
+    }
+}
+
+// What an eval has used so far of its limits
+#[derive(Default)]
+pub(crate) struct Budget {
+    pub limits: Limits,
+    // Whether there is anything to check, so unlimited code doesn't pay for it
+    pub active: bool,
+    instructions: u64,
+    output_bytes: usize,
+    started: Option<Instant>,
+    // Sizes of the values on the stack as of the last check, and the bytes
+    // held by the stack and by variables, only kept with a memory limit
+    sizes: Vec<usize>,
+    stack_bytes: usize,
+    variable_bytes: usize,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Self {
+        Budget {
+            active: limits.any(),
+            limits,
+            ..Budget::default()
+        }
+    }
+
+    // Start counting again for a new eval
+    pub fn reset(&mut self, stack: &[Type], variables: &HashMap<String, Type>) {
+        self.instructions = 0;
+        self.output_bytes = 0;
+        self.started = self.limits.max_time.map(|_| Instant::now());
+        self.recount(stack, variables);
+    }
+
+    // Measure all memory in use again, after the stack or variables were
+    // replaced as a whole
+    pub fn recount(&mut self, stack: &[Type], variables: &HashMap<String, Type>) {
+        self.sizes.clear();
+        self.stack_bytes = 0;
+        self.variable_bytes = 0;
+        if self.limits.max_memory.is_none() {
+            return;
+        }
+        self.measure(stack, 0);
+        self.variable_bytes = variables.iter().map(|(k, v)| k.len() + v.size()).sum();
+    }
+
+    // Measure the stack from `low` up, the values below it haven't changed.
+    // Returns the bytes measured.
+    fn measure(&mut self, stack: &[Type], low: usize) -> usize {
+        let low = low.min(stack.len()).min(self.sizes.len());
+        for size in self.sizes.drain(low..) {
+            self.stack_bytes -= size;
+        }
+        let mut measured = 0;
+        for value in &stack[low..] {
+            let size = value.size();
+            self.sizes.push(size);
+            measured += size;
+        }
+        self.stack_bytes += measured;
+        measured
+    }
+
+    // Account for a variable about to be set
+    pub fn store(&mut self, name: &str, old: Option<&Type>, new: &Type) {
+        if self.limits.max_memory.is_none() {
+            return;
+        }
+        if let Some(old) = old {
+            self.variable_bytes -= name.len() + old.size();
+        }
+        self.variable_bytes += name.len() + new.size();
+    }
+
+    // Refuse work that would need more than the memory left, before doing it
+    pub fn reserve(&self, bytes: usize) -> Result<()> {
+        if let Some(max) = self.limits.max_memory
+            && self.stack_bytes + self.variable_bytes + bytes > max
+        {
+            return Err(exceeded(format!("Memory limit of {} bytes exceeded", max)));
+        }
+        Ok(())
+    }
+
+    // Called after every instruction. Only the stack from `low` up can have
+    // changed since the last check.
+    pub fn check(&mut self, stack: &[Type], low: usize) -> Result<()> {
+        self.instructions += 1;
+        let limits = &self.limits;
+        if let Some(max) = limits.max_instructions
+            && self.instructions > max
+        {
+            return Err(exceeded(format!("Instruction limit of {} exceeded", max)));
+        }
+        if let Some(max) = limits.max_stack_depth
+            && stack.len() > max
+        {
+            return Err(exceeded(format!("Stack depth limit of {} exceeded", max)));
+        }
+
+        let mut measured = 0;
+        if let Some(max) = limits.max_memory {
+            measured = self.measure(stack, low);
+            if self.stack_bytes + self.variable_bytes > max {
+                return Err(exceeded(format!("Memory limit of {} bytes exceeded", max)));
+            }
+        }
+
+        let limits = &self.limits;
+        if let (Some(max), Some(started)) = (limits.max_time, self.started)
+            && (self.instructions.is_multiple_of(CHECK_INTERVAL) || measured > LARGE_MEASURE)
+            && started.elapsed() > max
+        {
+            return Err(exceeded(format!("Time limit of {:?} exceeded", max)));
+        }
+        Ok(())
+    }
+
+    // Account for output about to be written
+    pub fn output(&mut self, bytes: usize) -> Result<()> {
+        self.output_bytes += bytes;
+        if let Some(max) = self.limits.max_output_bytes
+            && self.output_bytes > max
+        {
+            return Err(exceeded(format!("Output limit of {} bytes exceeded", max)));
+        }
+        Ok(())
+    }
+}
+
+fn exceeded(message: String) -> Error {
+    ErrorKind::LimitExceeded(message).into()
+}
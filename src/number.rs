@@ -0,0 +1,414 @@
+use crate::bigint::BigInt;
+use crate::decimal::{Decimal, Rounding};
+use crate::error::{Error, ErrorKind, Result};
+use crate::rational::Rational;
+use crate::types::Type;
+
+// Arithmetic on two numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    // Remainder of Div
+    Mod,
+    // Remainder of division rounding towards zero, whatever the division mode
+    Rem,
+    Pow,
+}
+
+// Which way integer division rounds
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Division {
+    // Towards zero, the remainder has the sign of the dividend: -7 2 / is -3, rem -1
+    #[default]
+    Truncate,
+    // Towards negative infinity, the remainder has the sign of the divisor:
+    // -7 2 / is -4, mod 1
+    Floor,
+}
+
+// Largest result in bits that multiplying and pow compute. Multiplying takes
+// time quadratic in the size, so much larger ones would run for minutes.
+const MAX_BITS: u64 = 1 << 19;
+
+// Digits kept after the decimal point unless set_decimal_scale says otherwise
+pub const DEFAULT_DECIMAL_SCALE: u32 = 28;
+
+// Most digits a decimal result or $.N in format may keep after the point,
+// dividing to more would take too long
+pub const MAX_DECIMAL_SCALE: u32 = 4096;
+
+// How arithmetic behaves, changeable per interpreter
+#[derive(Debug, Clone)]
+pub struct NumberConfig {
+    // Integer overflow is an error instead of switching to big integers
+    pub strict: bool,
+    pub division: Division,
+    // Most digits after the point a decimal result keeps
+    pub decimal_scale: u32,
+    // How decimal results with more digits than that are rounded
+    pub rounding: Rounding,
+    // Literals like 0.1 are read as decimals instead of floats
+    pub decimal_literals: bool,
+}
+
+impl Default for NumberConfig {
+    fn default() -> Self {
+        NumberConfig {
+            strict: false,
+            division: Division::default(),
+            decimal_scale: DEFAULT_DECIMAL_SCALE,
+            rounding: Rounding::default(),
+            decimal_literals: false,
+        }
+    }
+}
+
+// Numbers widened to a common kind: int < decimal < rational < float
+enum Pair {
+    Int(i64, i64),
+    Big(BigInt, BigInt),
+    Decimal(Decimal, Decimal),
+    Rational(Rational, Rational),
+    Float(f64, f64),
+}
+
+fn pair(a: &Type, b: &Type) -> Result<Pair> {
+    Ok(match (a, b) {
+        (Type::Int(a), Type::Int(b)) => Pair::Int(*a, *b),
+        (Type::Int(_) | Type::BigInt(_), Type::Int(_) | Type::BigInt(_)) => {
+            Pair::Big(to_big(a), to_big(b))
+        }
+        (Type::Float(_), _) | (_, Type::Float(_)) => Pair::Float(to_float(a)?, to_float(b)?),
+        (Type::Rational(_), _) | (_, Type::Rational(_)) => {
+            Pair::Rational(to_rational(a)?, to_rational(b)?)
+        }
+        _ => Pair::Decimal(to_decimal(a)?, to_decimal(b)?),
+    })
+}
+
+fn to_big(value: &Type) -> BigInt {
+    match value {
+        Type::Int(n) => BigInt::from(*n),
+        Type::BigInt(n) => n.clone(),
+        _ => unreachable!(),
+    }
+}
+
+fn to_rational(value: &Type) -> Result<Rational> {
+    match value {
+        Type::Int(_) | Type::BigInt(_) => Ok(Rational::from(to_big(value))),
+        Type::Rational(r) => Ok(r.clone()),
+        Type::Decimal(d) => Ok(d.to_rational()),
+        other => Err(Error::type_mismatch("number", other)),
+    }
+}
+
+fn to_decimal(value: &Type) -> Result<Decimal> {
+    match value {
+        Type::Int(_) | Type::BigInt(_) => Ok(Decimal::from(to_big(value))),
+        Type::Decimal(d) => Ok(d.clone()),
+        other => Err(Error::type_mismatch("number", other)),
+    }
+}
+
+pub fn to_float(value: &Type) -> Result<f64> {
+    match value {
+        Type::Int(n) => Ok(*n as f64),
+        Type::BigInt(n) => Ok(n.to_f64()),
+        Type::Rational(r) => Ok(r.to_f64()),
+        Type::Decimal(d) => Ok(d.to_f64()),
+        Type::Float(f) => Ok(*f),
+        other => Err(Error::type_mismatch("number", other)),
+    }
+}
+
+// Apply an operation, a op b
+pub fn arith(op: Arith, a: &Type, b: &Type, config: &NumberConfig) -> Result<Type> {
+    let division = match op {
+        Arith::Div | Arith::Mod => Some(config.division),
+        Arith::Rem => Some(Division::Truncate),
+        _ => None,
+    };
+    let pair = pair(a, b)?;
+    // Division of rationals and decimals isn't whole, only their remainders
+    // go through div_mod
+    let whole = match pair {
+        Pair::Int(..) | Pair::Big(..) => true,
+        Pair::Decimal(..) | Pair::Rational(..) => op != Arith::Div,
+        Pair::Float(..) => false,
+    };
+    if let Some(division) = division
+        && whole
+    {
+        let (quotient, remainder) = div_mod(a, b, division, config)?;
+        return Ok(if op == Arith::Div {
+            quotient
+        } else {
+            remainder
+        });
+    }
+
+    match pair {
+        Pair::Int(x, y) => match int_arith(op, x, y)? {
+            Some(result) => Ok(result),
+            // Overflowed, redo it with big integers
+            None if config.strict => Err(ErrorKind::IntegerOverflow.into()),
+            None => big_arith(op, BigInt::from(x), BigInt::from(y)),
+        },
+        Pair::Big(x, y) => {
+            let result = big_arith(op, x, y)?;
+            if config.strict && matches!(result, Type::BigInt(_)) {
+                return Err(ErrorKind::IntegerOverflow.into());
+            }
+            Ok(result)
+        }
+        Pair::Decimal(x, y) => decimal_arith(op, x, y, config),
+        Pair::Rational(x, y) => rational_arith(op, x, y),
+        Pair::Float(x, y) => Ok(Type::Float(float_arith(op, x, y, config.division))),
+    }
+}
+
+// a / b without rounding: ints give a rational when they don't divide evenly
+pub fn ratio(a: &Type, b: &Type, config: &NumberConfig) -> Result<Type> {
+    match pair(a, b)? {
+        Pair::Int(..) | Pair::Big(..) => {
+            let ratio = Rational::new(to_big(a), to_big(b)).ok_or(ErrorKind::DivisionByZero)?;
+            Ok(Type::from(ratio))
+        }
+        _ => arith(Arith::Div, a, b, config),
+    }
+}
+
+// Quotient and remainder of a divided by b. Floats give a whole quotient too.
+pub fn div_mod(
+    a: &Type,
+    b: &Type,
+    division: Division,
+    config: &NumberConfig,
+) -> Result<(Type, Type)> {
+    match pair(a, b)? {
+        Pair::Int(_, 0) => Err(ErrorKind::DivisionByZero.into()),
+        Pair::Int(x, y) => match (x.checked_div(y), x.checked_rem(y)) {
+            (Some(quotient), Some(remainder)) => {
+                let (quotient, remainder) = if division == Division::Floor
+                    && remainder != 0
+                    && (remainder < 0) != (y < 0)
+                {
+                    (quotient - 1, remainder + y)
+                } else {
+                    (quotient, remainder)
+                };
+                Ok((Type::Int(quotient), Type::Int(remainder)))
+            }
+            // Only i64::MIN / -1
+            _ if config.strict => Err(ErrorKind::IntegerOverflow.into()),
+            _ => big_div_mod(BigInt::from(x), BigInt::from(y), division),
+        },
+        Pair::Big(x, y) => {
+            let result = big_div_mod(x, y, division)?;
+            if config.strict && matches!(result.0, Type::BigInt(_)) {
+                return Err(ErrorKind::IntegerOverflow.into());
+            }
+            Ok(result)
+        }
+        Pair::Decimal(x, y) => {
+            let ratio = x.to_rational().div(&y.to_rational());
+            let ratio = ratio.ok_or(ErrorKind::DivisionByZero)?;
+            let quotient = Decimal::from(match division {
+                Division::Truncate => ratio.trunc(),
+                Division::Floor => ratio.floor(),
+            });
+            let remainder = x.sub(&y.mul(&quotient));
+            Ok((Type::from(quotient), Type::from(remainder)))
+        }
+        Pair::Rational(x, y) => {
+            let ratio = x.div(&y).ok_or(ErrorKind::DivisionByZero)?;
+            let quotient = Rational::from(match division {
+                Division::Truncate => ratio.trunc(),
+                Division::Floor => ratio.floor(),
+            });
+            let remainder = x.sub(&y.mul(&quotient));
+            Ok((Type::from(quotient), Type::from(remainder)))
+        }
+        Pair::Float(x, y) => {
+            let quotient = match division {
+                Division::Truncate => (x / y).trunc(),
+                Division::Floor => (x / y).floor(),
+            };
+            Ok((Type::Float(quotient), Type::Float(x - y * quotient)))
+        }
+    }
+}
+
+fn big_div_mod(x: BigInt, y: BigInt, division: Division) -> Result<(Type, Type)> {
+    let result = match division {
+        Division::Truncate => x.div_rem(&y),
+        Division::Floor => x.div_mod_floor(&y),
+    };
+    let (quotient, remainder) = result.ok_or(ErrorKind::DivisionByZero)?;
+    Ok((Type::from(quotient), Type::from(remainder)))
+}
+
+// None when the result doesn't fit in an i64
+fn int_arith(op: Arith, x: i64, y: i64) -> Result<Option<Type>> {
+    let result = match op {
+        Arith::Add => x.checked_add(y),
+        Arith::Sub => x.checked_sub(y),
+        Arith::Mul => x.checked_mul(y),
+        // Done by div_mod
+        Arith::Div | Arith::Mod | Arith::Rem => unreachable!(),
+        // Negative powers aren't integers
+        Arith::Pow if y < 0 => return Ok(Some(Type::Float((x as f64).powf(y as f64)))),
+        Arith::Pow => u32::try_from(y).ok().and_then(|y| x.checked_pow(y)),
+    };
+    Ok(result.map(Type::Int))
+}
+
+fn big_arith(op: Arith, x: BigInt, y: BigInt) -> Result<Type> {
+    let result = match op {
+        Arith::Add => x.add(&y),
+        Arith::Sub => x.sub(&y),
+        Arith::Mul => {
+            check_bits(x.bits() + y.bits())?;
+            x.mul(&y)
+        }
+        Arith::Div | Arith::Mod | Arith::Rem => unreachable!(),
+        Arith::Pow => {
+            if y.is_negative() {
+                return Ok(Type::Float(x.to_f64().powf(y.to_f64())));
+            }
+            x.pow(pow_exponent(&y, x.bits())?)
+        }
+    };
+    Ok(Type::from(result))
+}
+
+fn rational_arith(op: Arith, x: Rational, y: Rational) -> Result<Type> {
+    // Size of the cross products, before they are reduced to lowest terms
+    let bits = |a: &BigInt, b: &BigInt| a.bits() + b.bits();
+    let (xn, xd, yn, yd) = (
+        x.numerator(),
+        x.denominator(),
+        y.numerator(),
+        y.denominator(),
+    );
+    match op {
+        Arith::Add | Arith::Sub => {
+            check_bits(bits(xn, yd).max(bits(yn, xd)).max(bits(xd, yd)) + 1)?
+        }
+        Arith::Mul => check_bits(bits(xn, yn).max(bits(xd, yd)))?,
+        Arith::Div => check_bits(bits(xn, yd).max(bits(xd, yn)))?,
+        _ => {}
+    }
+
+    let result = match op {
+        Arith::Add => x.add(&y),
+        Arith::Sub => x.sub(&y),
+        Arith::Mul => x.mul(&y),
+        Arith::Div => x.div(&y).ok_or(ErrorKind::DivisionByZero)?,
+        Arith::Mod | Arith::Rem => unreachable!(),
+        // Only whole powers stay exact
+        Arith::Pow if !y.is_integer() => return Ok(Type::Float(x.to_f64().powf(y.to_f64()))),
+        Arith::Pow => {
+            let exponent = y.numerator();
+            let bits = x.numerator().bits().max(x.denominator().bits());
+            let result = x.pow(pow_exponent(exponent, bits)?);
+            if exponent.is_negative() {
+                Rational::from(BigInt::from(1))
+                    .div(&result)
+                    .ok_or(ErrorKind::DivisionByZero)?
+            } else {
+                result
+            }
+        }
+    };
+    Ok(Type::from(result))
+}
+
+// Results keep at most the configured number of digits after the point
+fn decimal_arith(op: Arith, x: Decimal, y: Decimal, config: &NumberConfig) -> Result<Type> {
+    let (scale, rounding) = (config.decimal_scale, config.rounding);
+    let result = match op {
+        Arith::Add => x.add(&y),
+        Arith::Sub => x.sub(&y),
+        Arith::Mul => {
+            check_bits(x.mantissa().bits() + y.mantissa().bits())?;
+            x.mul(&y)
+        }
+        Arith::Div => x
+            .div(&y, scale, rounding)
+            .ok_or(ErrorKind::DivisionByZero)?,
+        Arith::Mod | Arith::Rem => unreachable!(),
+        // Only whole powers stay exact
+        Arith::Pow => {
+            let exponent = y.to_rational();
+            if !exponent.is_integer() {
+                return Ok(Type::Float(x.to_f64().powf(y.to_f64())));
+            }
+            let exponent = exponent.numerator();
+            let power = x
+                .pow(pow_exponent(exponent, x.mantissa().bits())?)
+                .ok_or_else(|| Error::custom(format!("Exponent {} is too large", exponent)))?;
+            if exponent.is_negative() {
+                let one = Decimal::from(BigInt::from(1));
+                one.div(&power, scale, rounding)
+                    .ok_or(ErrorKind::DivisionByZero)?
+            } else {
+                power
+            }
+        }
+    };
+    Ok(Type::Decimal(result.round(scale, rounding)))
+}
+
+// |exponent| as a power to raise a base of `base_bits` bits to, if the
+// result stays within MAX_BITS
+fn pow_exponent(exponent: &BigInt, base_bits: u64) -> Result<u32> {
+    let power = exponent
+        .abs()
+        .to_i64()
+        .and_then(|e| u32::try_from(e).ok())
+        .ok_or_else(|| Error::custom(format!("Exponent {} is too large", exponent)))?;
+    // 0, 1 and -1 stay small whatever the exponent
+    if base_bits > 1 {
+        check_bits(base_bits * power as u64)?;
+    }
+    Ok(power)
+}
+
+// A scale for decimal results, refused beyond MAX_DECIMAL_SCALE
+pub fn decimal_scale(scale: i64) -> Result<u32> {
+    match u32::try_from(scale) {
+        Ok(scale) if scale <= MAX_DECIMAL_SCALE => Ok(scale),
+        _ => {
+            let message = format!(
+                "Decimal scale {} is not between 0 and {}",
+                scale, MAX_DECIMAL_SCALE
+            );
+            Err(Error::custom(message))
+        }
+    }
+}
+
+fn check_bits(bits: u64) -> Result<()> {
+    if bits > MAX_BITS {
+        let message = format!("Result would have more than {} bits", MAX_BITS);
+        return Err(Error::custom(message));
+    }
+    Ok(())
+}
+
+fn float_arith(op: Arith, x: f64, y: f64, division: Division) -> f64 {
+    match op {
+        Arith::Add => x + y,
+        Arith::Sub => x - y,
+        Arith::Mul => x * y,
+        Arith::Div => x / y,
+        Arith::Mod if division == Division::Floor => x - y * (x / y).floor(),
+        Arith::Mod | Arith::Rem => x % y,
+        Arith::Pow => x.powf(y),
+    }
+}
@@ -0,0 +1,305 @@
+use crate::bigint::{BigInt, scale_by_power_of_two};
+use std::cmp::Ordering;
+use std::fmt;
+
+// Exact fraction, always in lowest terms with a positive denominator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    // None when the denominator is zero
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Rational> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let gcd = numerator.gcd(&denominator);
+        let (mut numerator, _) = numerator.div_rem(&gcd).unwrap();
+        let (mut denominator, _) = denominator.div_rem(&gcd).unwrap();
+        if denominator.is_negative() {
+            numerator = numerator.neg();
+            denominator = denominator.neg();
+        }
+        Some(Rational {
+            numerator,
+            denominator,
+        })
+    }
+
+    // Parse a literal like 1/3 or -22/7. None if the text isn't one,
+    // Some(None) if its denominator is zero.
+    pub fn parse(text: &str) -> Option<Option<Rational>> {
+        let (numerator, denominator) = text.split_once('/')?;
+        if denominator.starts_with('-') {
+            return None;
+        }
+        let numerator = BigInt::parse(numerator)?;
+        let denominator = BigInt::parse(denominator)?;
+        Some(Rational::new(numerator, denominator))
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == BigInt::from(1)
+    }
+
+    // Bytes used for the digits
+    pub fn heap_size(&self) -> usize {
+        self.numerator.heap_size() + self.denominator.heap_size()
+    }
+
+    // The exact value of a finite float, None for infinities and NaN
+    pub fn from_f64(f: f64) -> Option<Rational> {
+        if !f.is_finite() {
+            return None;
+        }
+        // A 53 bit mantissa times a power of two
+        let bits = f.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let mut mantissa = (bits & ((1 << 52) - 1)) as i64;
+        let exponent = if exponent == 0 {
+            // Subnormal, no implicit leading bit
+            -1074
+        } else {
+            mantissa |= 1 << 52;
+            exponent - 1075
+        };
+        if f < 0.0 {
+            mantissa = -mantissa;
+        }
+        let mantissa = BigInt::from(mantissa);
+        let one = BigInt::from(1);
+        if exponent >= 0 {
+            Some(Rational::from(mantissa.shift_left(exponent as u64)))
+        } else {
+            Rational::new(mantissa, one.shift_left(-exponent as u64))
+        }
+    }
+
+    // Rounded to the nearest float, even when both parts are too big for one.
+    // The quotient is taken to at least 65 bits, with a bit set below them for
+    // a remainder, so it rounds the same as the exact value. Results too small
+    // for a normal float are rounded to a multiple of 2^-1074 directly.
+    pub fn to_f64(&self) -> f64 {
+        if self.numerator.is_zero() {
+            return 0.0;
+        }
+        let one = BigInt::from(1);
+        let numerator = self.numerator.abs();
+        let mut exponent = self.denominator.bits() as i64 - numerator.bits() as i64 + 65;
+        let subnormal = exponent > 1086;
+        if subnormal {
+            exponent = 1074;
+        }
+        let (numerator, denominator) = if exponent >= 0 {
+            (
+                numerator.shift_left(exponent as u64),
+                self.denominator.clone(),
+            )
+        } else {
+            (numerator, self.denominator.shift_left(-exponent as u64))
+        };
+
+        let (mut quotient, remainder) = numerator.div_rem(&denominator).unwrap();
+        if subnormal {
+            // Half-even, like float arithmetic
+            let twice = remainder.shift_left(1);
+            if twice > denominator || (twice == denominator && !quotient.is_even()) {
+                quotient = quotient.add(&one);
+            }
+        } else if !remainder.is_zero() {
+            quotient = quotient.shift_left(1).add(&one);
+            exponent += 1;
+        }
+        let magnitude = scale_by_power_of_two(quotient.to_f64(), -exponent);
+        if self.numerator.is_negative() {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn add(&self, other: &Rational) -> Rational {
+        let numerator = self
+            .numerator
+            .mul(&other.denominator)
+            .add(&other.numerator.mul(&self.denominator));
+        Rational::new(numerator, self.denominator.mul(&other.denominator)).unwrap()
+    }
+
+    pub fn sub(&self, other: &Rational) -> Rational {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Rational) -> Rational {
+        let numerator = self.numerator.mul(&other.numerator);
+        Rational::new(numerator, self.denominator.mul(&other.denominator)).unwrap()
+    }
+
+    // None when dividing by zero
+    pub fn div(&self, other: &Rational) -> Option<Rational> {
+        let numerator = self.numerator.mul(&other.denominator);
+        Rational::new(numerator, self.denominator.mul(&other.numerator))
+    }
+
+    pub fn neg(&self) -> Rational {
+        Rational {
+            numerator: self.numerator.neg(),
+            denominator: self.denominator.clone(),
+        }
+    }
+
+    // Whole number rounded towards zero
+    pub fn trunc(&self) -> BigInt {
+        self.numerator.div_rem(&self.denominator).unwrap().0
+    }
+
+    // Whole number rounded towards negative infinity
+    pub fn floor(&self) -> BigInt {
+        self.numerator.div_mod_floor(&self.denominator).unwrap().0
+    }
+
+    pub fn pow(&self, exponent: u32) -> Rational {
+        Rational {
+            numerator: self.numerator.pow(exponent),
+            denominator: self.denominator.pow(exponent),
+        }
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(n: BigInt) -> Self {
+        Rational {
+            numerator: n,
+            denominator: BigInt::from(1),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are positive, so cross multiplying keeps the order
+        let left = self.numerator.mul(&other.denominator);
+        let right = other.numerator.mul(&self.denominator);
+        left.cmp(&right)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(text: &str) -> Rational {
+        Rational::parse(text).unwrap().unwrap()
+    }
+
+    fn parts(r: &Rational) -> (String, String) {
+        (r.numerator().to_string(), r.denominator().to_string())
+    }
+
+    #[test]
+    fn new_reduces_and_keeps_the_denominator_positive() {
+        let r = Rational::new(BigInt::from(4), BigInt::from(-6)).unwrap();
+        assert_eq!(parts(&r), ("-2".to_string(), "3".to_string()));
+        let r = Rational::new(BigInt::from(-4), BigInt::from(-6)).unwrap();
+        assert_eq!(parts(&r), ("2".to_string(), "3".to_string()));
+        let zero = Rational::new(BigInt::from(0), BigInt::from(-5)).unwrap();
+        assert_eq!(parts(&zero), ("0".to_string(), "1".to_string()));
+        assert!(Rational::new(BigInt::from(1), BigInt::from(0)).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_negative_denominators() {
+        assert_eq!(
+            rational("-22/7"),
+            Rational::new(BigInt::from(-22), BigInt::from(7)).unwrap()
+        );
+        assert_eq!(Rational::parse("1/-2"), None);
+        assert_eq!(Rational::parse("1/0"), Some(None));
+        assert_eq!(Rational::parse("12"), None);
+    }
+
+    #[test]
+    fn arithmetic_stays_in_lowest_terms() {
+        assert_eq!(
+            parts(&rational("1/6").add(&rational("1/3"))),
+            ("1".into(), "2".into())
+        );
+        assert_eq!(
+            parts(&rational("1/3").sub(&rational("1/3"))),
+            ("0".into(), "1".into())
+        );
+        assert_eq!(
+            parts(&rational("2/3").mul(&rational("3/4"))),
+            ("1".into(), "2".into())
+        );
+        let quotient = rational("1/2").div(&rational("-3/4")).unwrap();
+        assert_eq!(parts(&quotient), ("-2".into(), "3".into()));
+        assert!(rational("1/2").div(&rational("0/1")).is_none());
+        assert!(rational("3/1").is_integer());
+    }
+
+    #[test]
+    fn floor_and_trunc_differ_for_negatives() {
+        assert_eq!(rational("-7/2").trunc(), BigInt::from(-3));
+        assert_eq!(rational("-7/2").floor(), BigInt::from(-4));
+        assert_eq!(rational("7/2").floor(), BigInt::from(3));
+    }
+
+    #[test]
+    fn ordering_cross_multiplies() {
+        assert!(rational("-1/2") < rational("1/3"));
+        assert!(rational("1/3") < rational("1/2"));
+        assert_eq!(rational("2/4").cmp(&rational("1/2")), Ordering::Equal);
+    }
+
+    #[test]
+    fn to_f64_handles_huge_parts() {
+        let big = BigInt::from(10).pow(400);
+        let third = Rational::new(big.add(&BigInt::from(1)), big.mul(&BigInt::from(3))).unwrap();
+        assert_eq!(third.to_f64(), 1.0 / 3.0);
+        assert_eq!(Rational::from(big.clone()).to_f64(), f64::INFINITY);
+        let tiny = Rational::new(BigInt::from(-1), big).unwrap();
+        assert!(tiny.to_f64() == 0.0 && tiny.to_f64().is_sign_negative());
+        assert_eq!(rational("-22/7").to_f64(), -22.0 / 7.0);
+        // Results below the normal range round once, at the last subnormal bit
+        let smallest = Rational::new(BigInt::from(3), BigInt::from(1).shift_left(1075)).unwrap();
+        assert_eq!(smallest.to_f64(), 2.0 * f64::from_bits(1));
+    }
+
+    #[test]
+    fn from_f64_is_exact() {
+        let tenth = Rational::from_f64(0.1).unwrap();
+        assert_eq!(
+            parts(&tenth),
+            ("3602879701896397".into(), "36028797018963968".into())
+        );
+        assert_eq!(tenth.to_f64(), 0.1);
+        for f in [1.5, -2.75, 1e300, f64::MIN_POSITIVE, f64::from_bits(1)] {
+            assert_eq!(Rational::from_f64(f).unwrap().to_f64(), f);
+        }
+        assert!(Rational::from_f64(f64::NAN).is_none());
+        assert!(Rational::from_f64(f64::INFINITY).is_none());
+    }
+}
@@ -1,15 +1,94 @@
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+// Where a token was found, lines and columns start at 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    // Length in characters
+    pub len: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Word,
+    // A quoted string literal, quotes stripped
+    Str,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+    // The file the token was read from, if any
+    pub file: Option<Rc<Path>>,
+    // All of the text the token was read from, for showing it in errors
+    pub source: Rc<str>,
+}
+
+impl Token {
+    // True for an unquoted word spelled `text`
+    pub fn is(&self, text: &str) -> bool {
+        self.kind == TokenKind::Word && self.text == text
+    }
+}
+
 // Tokenizer
-pub fn tokenize(input: &str) -> Vec<String> {
+pub fn tokenize(input: &str, file: Option<Rc<Path>>) -> Vec<Token> {
     let mut tokens = Vec::new();
     let mut current = String::new();
+    let source: Rc<str> = Rc::from(input);
     let mut in_string = false;
 
+    let mut line = 1;
+    let mut column = 0;
+    // Position of the first character of `current`
+    let mut start = Span {
+        line: 1,
+        column: 1,
+        len: 0,
+    };
+
+    let mut finish = |current: &mut String, kind, start: Span| {
+        let mut len = current.chars().count();
+        if kind == TokenKind::Str {
+            // Include the quotes
+            len += 2;
+        }
+        tokens.push(Token {
+            kind,
+            text: std::mem::take(current),
+            span: Span { len, ..start },
+            file: file.clone(),
+            source: source.clone(),
+        });
+    };
+
     for ch in input.chars() {
+        column += 1;
+
         if ch == '"' {
             if in_string {
-                // End of string - mark it with STR: prefix
-                tokens.push(format!("STR:{}", current));
-                current.clear();
+                // End of string, the span covers both quotes
+                finish(&mut current, TokenKind::Str, start);
+            } else {
+                if !current.is_empty() {
+                    finish(&mut current, TokenKind::Word, start);
+                }
+                start = Span {
+                    line,
+                    column,
+                    len: 0,
+                };
             }
             in_string = !in_string;
         } else if in_string {
@@ -18,17 +97,28 @@ pub fn tokenize(input: &str) -> Vec<String> {
         } else if ch.is_whitespace() {
             // Outside quotes, whitespace separates tokens
             if !current.is_empty() {
-                tokens.push(current.clone());
-                current.clear();
+                finish(&mut current, TokenKind::Word, start);
             }
         } else {
             // Regular character
+            if current.is_empty() {
+                start = Span {
+                    line,
+                    column,
+                    len: 0,
+                };
+            }
             current.push(ch);
         }
+
+        if ch == '\n' {
+            line += 1;
+            column = 0;
+        }
     }
 
     if !current.is_empty() {
-        tokens.push(current);
+        finish(&mut current, TokenKind::Word, start);
     }
 
     tokens
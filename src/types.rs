@@ -1,7 +1,298 @@
+use crate::bigint::BigInt;
+use crate::compiler::Code;
+use crate::decimal::Decimal;
+use crate::rational::Rational;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
 #[derive(Debug, Clone)]
 pub enum Type {
     Int(i64),
+    // Only for integers that don't fit in an Int
+    BigInt(BigInt),
+    // Only for fractions that aren't whole numbers
+    Rational(Rational),
+    // Base 10 fixed-point, keeps its scale: 1.50d
+    Decimal(Decimal),
     Float(f64),
     String(String),
     Bool(bool),
+    List(Vec<Type>),
+    // String keys, kept sorted so printing is deterministic
+    Map(BTreeMap<String, Type>),
+    // Anonymous compiled code: [: dup * ;]
+    Quotation(Rc<Code>),
+}
+
+impl Type {
+    // Name used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Type::Int(_) | Type::BigInt(_) => "int",
+            Type::Rational(_) => "rational",
+            Type::Decimal(_) => "decimal",
+            Type::Float(_) => "float",
+            Type::String(_) => "string",
+            Type::Bool(_) => "bool",
+            Type::List(_) => "list",
+            Type::Map(_) => "map",
+            Type::Quotation(_) => "quotation",
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Type::Int(_) | Type::BigInt(_) | Type::Rational(_) | Type::Decimal(_) | Type::Float(_)
+        )
+    }
+
+    // Approximate bytes the value takes up, quotations are shared and count once
+    pub fn size(&self) -> usize {
+        let heap = match self {
+            Type::String(s) => s.len(),
+            Type::BigInt(n) => n.heap_size(),
+            Type::Rational(r) => r.heap_size(),
+            Type::Decimal(d) => d.heap_size(),
+            Type::List(items) => items.iter().map(Type::size).sum(),
+            Type::Map(map) => map.iter().map(|(k, v)| k.len() + v.size()).sum(),
+            _ => 0,
+        };
+        std::mem::size_of::<Type>() + heap
+    }
+
+    // Value equality, ints and floats compare by value
+    pub fn equals(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::List(a), Type::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.equals(y))
+            }
+            (Type::Map(a), Type::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.equals(vb))
+            }
+            (Type::Quotation(a), Type::Quotation(b)) => Rc::ptr_eq(a, b),
+            _ => self.compare(other) == Some(Ordering::Equal),
+        }
+    }
+
+    // Ordering between values of the same kind, None if they can't be compared
+    pub fn compare(&self, other: &Type) -> Option<Ordering> {
+        match (self, other) {
+            (Type::Int(a), Type::Int(b)) => Some(a.cmp(b)),
+            (Type::Float(a), Type::Float(b)) => a.partial_cmp(b),
+            // Ints up to 2^53 are exact as floats, others are compared exactly below
+            (Type::Int(a), Type::Float(b)) if a.unsigned_abs() <= 1 << 53 => {
+                (*a as f64).partial_cmp(b)
+            }
+            (Type::Float(a), Type::Int(b)) if b.unsigned_abs() <= 1 << 53 => {
+                a.partial_cmp(&(*b as f64))
+            }
+            (Type::BigInt(a), Type::BigInt(b)) => Some(a.cmp(b)),
+            (Type::Int(a), Type::BigInt(b)) => Some(BigInt::from(*a).cmp(b)),
+            (Type::BigInt(a), Type::Int(b)) => Some(a.cmp(&BigInt::from(*b))),
+            (Type::Decimal(a), Type::Decimal(b)) => Some(a.cmp(b)),
+            _ if self.is_number() && other.is_number() => self.compare_numbers(other),
+            (Type::String(a), Type::String(b)) => Some(a.cmp(b)),
+            (Type::Bool(a), Type::Bool(b)) => Some(a.cmp(b)),
+            (Type::List(a), Type::List(b)) => {
+                for (x, y) in a.iter().zip(b) {
+                    match x.compare(y)? {
+                        Ordering::Equal => continue,
+                        order => return Some(order),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            _ => None,
+        }
+    }
+
+    // Order between any two values: bools < numbers < strings < lists < maps
+    // < quotations. Values of a kind compare as with `compare`, NaN sorts
+    // after the other numbers and quotations by identity.
+    pub fn total_cmp(&self, other: &Type) -> Ordering {
+        let kind = self.kind_rank().cmp(&other.kind_rank());
+        if kind != Ordering::Equal {
+            return kind;
+        }
+        match (self, other) {
+            (Type::List(a), Type::List(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| x.total_cmp(y))
+                .find(|order| *order != Ordering::Equal)
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Type::Map(a), Type::Map(b)) => a
+                .iter()
+                .zip(b)
+                .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| va.total_cmp(vb)))
+                .find(|order| *order != Ordering::Equal)
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Type::Quotation(a), Type::Quotation(b)) => Rc::as_ptr(a).cmp(&Rc::as_ptr(b)),
+            _ => self
+                .compare(other)
+                .unwrap_or_else(|| self.is_nan().cmp(&other.is_nan())),
+        }
+    }
+
+    fn kind_rank(&self) -> u8 {
+        match self {
+            Type::Bool(_) => 0,
+            Type::Int(_)
+            | Type::BigInt(_)
+            | Type::Rational(_)
+            | Type::Decimal(_)
+            | Type::Float(_) => 1,
+            Type::String(_) => 2,
+            Type::List(_) => 3,
+            Type::Map(_) => 4,
+            Type::Quotation(_) => 5,
+        }
+    }
+
+    fn is_nan(&self) -> bool {
+        matches!(self, Type::Float(f) if f.is_nan())
+    }
+
+    // Numbers of different kinds compare by their exact values, finite floats
+    // included, so equality stays transitive and sorting consistent
+    fn compare_numbers(&self, other: &Type) -> Option<Ordering> {
+        let exact = |value: &Type| match value {
+            Type::Int(n) => Some(Rational::from(BigInt::from(*n))),
+            Type::BigInt(n) => Some(Rational::from(n.clone())),
+            Type::Rational(r) => Some(r.clone()),
+            Type::Decimal(d) => Some(d.to_rational()),
+            Type::Float(f) => Rational::from_f64(*f),
+            _ => None,
+        };
+        match (exact(self), exact(other)) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            // One side is an infinity or NaN, which compares with any finite
+            // number as it does with zero
+            _ => {
+                let float = |value: &Type| match value {
+                    Type::Float(f) => *f,
+                    _ => 0.0,
+                };
+                float(self).partial_cmp(&float(other))
+            }
+        }
+    }
+
+    // How the value is shown on the stack, strings are quoted
+    pub fn repr(&self) -> String {
+        match self {
+            Type::String(s) => format!("\"{}\"", s),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Int(n) => write!(f, "{}", n),
+            Type::BigInt(n) => write!(f, "{}", n),
+            Type::Rational(r) => write!(f, "{}", r),
+            Type::Decimal(d) => write!(f, "{}", d),
+            Type::Float(x) => write!(f, "{}", x),
+            Type::String(s) => write!(f, "{}", s),
+            Type::Bool(b) => write!(f, "{}", b),
+            Type::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item.repr())?;
+                }
+                write!(f, "]")
+            }
+            Type::Map(map) => {
+                write!(f, "#[")?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{}\": {}", key, value.repr())?;
+                }
+                write!(f, "]")
+            }
+            Type::Quotation(code) => write!(f, "{:?}", code),
+        }
+    }
+}
+
+impl From<i64> for Type {
+    fn from(n: i64) -> Self {
+        Type::Int(n)
+    }
+}
+
+// Big integers that fit become plain ints
+impl From<BigInt> for Type {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(n) => Type::Int(n),
+            None => Type::BigInt(n),
+        }
+    }
+}
+
+// Whole rationals become ints
+impl From<Rational> for Type {
+    fn from(r: Rational) -> Self {
+        if r.is_integer() {
+            Type::from(r.numerator().clone())
+        } else {
+            Type::Rational(r)
+        }
+    }
+}
+
+impl From<Decimal> for Type {
+    fn from(d: Decimal) -> Self {
+        Type::Decimal(d)
+    }
+}
+
+impl From<f64> for Type {
+    fn from(f: f64) -> Self {
+        Type::Float(f)
+    }
+}
+
+impl From<bool> for Type {
+    fn from(b: bool) -> Self {
+        Type::Bool(b)
+    }
+}
+
+impl From<String> for Type {
+    fn from(s: String) -> Self {
+        Type::String(s)
+    }
+}
+
+impl From<&str> for Type {
+    fn from(s: &str) -> Self {
+        Type::String(s.to_string())
+    }
+}
+
+impl From<Vec<Type>> for Type {
+    fn from(items: Vec<Type>) -> Self {
+        Type::List(items)
+    }
+}
+
+impl From<BTreeMap<String, Type>> for Type {
+    fn from(map: BTreeMap<String, Type>) -> Self {
+        Type::Map(map)
+    }
 }
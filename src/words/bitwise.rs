@@ -0,0 +1,74 @@
+use crate::error::{Error, Result};
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+// Bit operations work on 64-bit two's complement ints, ints from 2^63 up to
+// 2^64 - 1 such as 0xFFFFFFFFFFFFFFFF are taken as their bit pattern
+pub fn register_bitwise_words(interp: &mut Interpreter) {
+    interp.register("band", |interp| bit_op(interp, |a, b| a & b));
+    interp.register("bor", |interp| bit_op(interp, |a, b| a | b));
+    interp.register("bxor", |interp| bit_op(interp, |a, b| a ^ b));
+
+    interp.register("binvert", |interp| {
+        let n = pop_bits(interp)?;
+        interp.stack.push(Type::Int(!n));
+        Ok(())
+    });
+
+    // n count -- n ; bits shifted out are lost, 64 or more leave nothing
+    interp.register("lshift", |interp| {
+        let count = pop_shift(interp)?;
+        let n = pop_bits(interp)?;
+        interp
+            .stack
+            .push(Type::Int(n.checked_shl(count).unwrap_or(0)));
+        Ok(())
+    });
+    // Logical shift, zeros come in at the top
+    interp.register("rshift", |interp| {
+        let count = pop_shift(interp)?;
+        let n = pop_bits(interp)? as u64;
+        interp
+            .stack
+            .push(Type::Int(n.checked_shr(count).unwrap_or(0) as i64));
+        Ok(())
+    });
+    // Arithmetic shift, the sign bit is copied in at the top
+    interp.register("arshift", |interp| {
+        let count = pop_shift(interp)?;
+        let n = pop_bits(interp)?;
+        interp.stack.push(Type::Int(n >> count.min(63)));
+        Ok(())
+    });
+
+    // Number of set bits
+    interp.register("popcount", |interp| {
+        let n = pop_bits(interp)?;
+        interp.stack.push(Type::Int(n.count_ones() as i64));
+        Ok(())
+    });
+}
+
+fn bit_op(interp: &mut Interpreter, op: fn(i64, i64) -> i64) -> Result<()> {
+    let b = pop_bits(interp)?;
+    let a = pop_bits(interp)?;
+    interp.stack.push(Type::Int(op(a, b)));
+    Ok(())
+}
+
+// An int as 64 bits, unsigned ones above i64::MAX wrap around
+fn pop_bits(interp: &mut Interpreter) -> Result<i64> {
+    match interp.pop()? {
+        Type::Int(n) => Ok(n),
+        Type::BigInt(n) => match n.to_u64() {
+            Some(bits) => Ok(bits as i64),
+            None => Err(Error::custom(format!("{} does not fit in 64 bits", n))),
+        },
+        other => Err(Error::type_mismatch("int", &other)),
+    }
+}
+
+fn pop_shift(interp: &mut Interpreter) -> Result<u32> {
+    let count = interp.pop_int()?;
+    u32::try_from(count).map_err(|_| Error::custom(format!("Invalid shift count {}", count)))
+}
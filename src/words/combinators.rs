@@ -0,0 +1,140 @@
+use crate::error::Error;
+use crate::interpreter::Interpreter;
+use crate::types::Type;
+
+pub fn register_combinator_words(interp: &mut Interpreter) {
+    // Run a quotation
+    // quot --
+    interp.register("call", |interp| {
+        let quot = interp.pop_quotation()?;
+        interp.run(&quot)
+    });
+
+    // Run a quotation n times
+    // n quot --
+    interp.register("times", |interp| {
+        let quot = interp.pop_quotation()?;
+        let n = interp.pop_int()?;
+
+        for _ in 0..n {
+            interp.run(&quot)?;
+        }
+        Ok(())
+    });
+
+    // Run a quotation on every item, or on every key and value of a map
+    // list quot --
+    // map quot --
+    interp.register("each", |interp| {
+        let quot = interp.pop_quotation()?;
+
+        match interp.pop()? {
+            Type::List(items) => {
+                for item in items {
+                    interp.stack.push(item);
+                    interp.run(&quot)?;
+                }
+            }
+            Type::Map(map) => {
+                for (key, value) in map {
+                    interp.stack.push(Type::String(key));
+                    interp.stack.push(value);
+                    interp.run(&quot)?;
+                }
+            }
+            other => return Err(Error::type_mismatch("list or map", &other)),
+        }
+        Ok(())
+    });
+
+    // Replace every item with what the quotation leaves for it
+    // list quot -- list
+    interp.register("map", |interp| {
+        let quot = interp.pop_quotation()?;
+        let items = interp.pop_list()?;
+
+        let mut mapped = Vec::with_capacity(items.len());
+        for item in items {
+            interp.stack.push(item);
+            interp.run(&quot)?;
+            mapped.push(interp.pop()?);
+        }
+        interp.stack.push(Type::List(mapped));
+        Ok(())
+    });
+
+    // Keep the items the quotation answers true for
+    // list quot -- list
+    interp.register("filter", |interp| {
+        let quot = interp.pop_quotation()?;
+        let items = interp.pop_list()?;
+
+        let mut kept = Vec::new();
+        for item in items {
+            interp.stack.push(item.clone());
+            interp.run(&quot)?;
+            if interp.pop_bool()? {
+                kept.push(item);
+            }
+        }
+        interp.stack.push(Type::List(kept));
+        Ok(())
+    });
+
+    // Combine the items from left to right, starting with an initial value
+    // list init quot -- value
+    interp.register("fold", |interp| {
+        let quot = interp.pop_quotation()?;
+        let init = interp.pop()?;
+        let items = interp.pop_list()?;
+
+        interp.stack.push(init);
+        for item in items {
+            interp.stack.push(item);
+            interp.run(&quot)?;
+        }
+        Ok(())
+    });
+
+    // Like fold, starting with the first item
+    // list quot -- value
+    interp.register("reduce", |interp| {
+        let quot = interp.pop_quotation()?;
+        let mut items = interp.pop_list()?.into_iter();
+
+        let first = items
+            .next()
+            .ok_or_else(|| Error::custom("Cannot reduce an empty list"))?;
+        interp.stack.push(first);
+        for item in items {
+            interp.stack.push(item);
+            interp.run(&quot)?;
+        }
+        Ok(())
+    });
+
+    // Apply two quotations to the same value
+    // x p q -- p(x) q(x)
+    interp.register("bi", |interp| {
+        let q = interp.pop_quotation()?;
+        let p = interp.pop_quotation()?;
+        let x = interp.pop()?;
+
+        interp.stack.push(x.clone());
+        interp.run(&p)?;
+        interp.stack.push(x);
+        interp.run(&q)
+    });
+
+    // Run a quotation on a value and keep the value
+    // x quot -- quot(x) x
+    interp.register("keep", |interp| {
+        let quot = interp.pop_quotation()?;
+        let x = interp.pop()?;
+
+        interp.stack.push(x.clone());
+        interp.run(&quot)?;
+        interp.stack.push(x);
+        Ok(())
+    });
+}
@@ -1,91 +1,162 @@
-use crate::interpreter::{Interpreter, Word};
+use crate::error::{Error, Result};
+use crate::interpreter::Interpreter;
 use crate::types::Type;
+use std::cmp::Ordering;
 
 pub fn register_comparison_words(interp: &mut Interpreter) {
     // Comparison Operations
-    //Equal
-    interp.dictionary.insert(
-        "and".to_string(),
-        Word::Native(|interp| {
-            let tos = interp.stack.pop().ok_or("Stack underflow!")?;
-            let nos = interp.stack.pop().ok_or("Stack underflow!")?;
+    // Equal, numbers by value and values of different types never
+    interp.register("=", |interp| {
+        let tos = interp.pop()?;
+        let nos = interp.pop()?;
+        interp.stack.push(Type::Bool(nos.equals(&tos)));
+        Ok(())
+    });
+    // Not Equal
+    interp.register("<>", not_equal);
+    interp.register("!=", not_equal);
 
-            let result = match (nos, tos) {
-                (Type::Int(a), Type::Int(b)) => a == b,
-                (Type::Float(a), Type::Float(b)) => a == b,
-                (Type::String(a), Type::String(b)) => a == b,
-                (Type::Bool(a), Type::Bool(b)) => a == b,
-                // Mixed int/float comparison
-                (Type::Int(a), Type::Float(b)) => (a as f64) == b,
-                (Type::Float(a), Type::Int(b)) => a == (b as f64),
-                // Different types are not equal
-                _ => false,
-            };
-            interp.stack.push(Type::Bool(result));
-            Ok(())
-        }),
-    );
-    //Not Equal
-    interp.dictionary.insert(
-        "!=".to_string(),
-        Word::Native(|interp| {
-            let tos = interp.stack.pop().ok_or("Stack underflow!")?;
-            let nos = interp.stack.pop().ok_or("Stack underflow!")?;
-
-            let result = match (nos, tos) {
-                (Type::Int(a), Type::Int(b)) => a != b,
-                (Type::Float(a), Type::Float(b)) => a != b,
-                (Type::String(a), Type::String(b)) => a != b,
-                (Type::Bool(a), Type::Bool(b)) => a != b,
-                // Mixed int/float comparison
-                (Type::Int(a), Type::Float(b)) => (a as f64) != b,
-                (Type::Float(a), Type::Int(b)) => a != (b as f64),
-                // Different types are not equal
-                _ => false,
-            };
-            interp.stack.push(Type::Bool(result));
-            Ok(())
-        }),
-    );
     // Less than
-    interp.dictionary.insert(
-        "<".to_string(),
-        Word::Native(|interp| {
-            let (tos, _) = interp.pop_number()?;
-            let (nos, _) = interp.pop_number()?;
-            interp.stack.push(Type::Bool(nos < tos));
-            Ok(())
-        }),
-    );
+    interp.register("<", |interp| {
+        let order = pop_order(interp)?;
+        interp
+            .stack
+            .push(Type::Bool(matches!(order, Some(Ordering::Less))));
+        Ok(())
+    });
     // Less than or equal
-    interp.dictionary.insert(
-        "<=".to_string(),
-        Word::Native(|interp| {
-            let (tos, _) = interp.pop_number()?;
-            let (nos, _) = interp.pop_number()?;
-            interp.stack.push(Type::Bool(nos <= tos));
-            Ok(())
-        }),
-    );
+    interp.register("<=", |interp| {
+        let order = pop_order(interp)?;
+        interp.stack.push(Type::Bool(matches!(
+            order,
+            Some(Ordering::Less | Ordering::Equal)
+        )));
+        Ok(())
+    });
 
     // Greater than
-    interp.dictionary.insert(
-        ">".to_string(),
-        Word::Native(|interp| {
-            let (tos, _) = interp.pop_number()?;
-            let (nos, _) = interp.pop_number()?;
-            interp.stack.push(Type::Bool(nos > tos));
-            Ok(())
-        }),
-    );
+    interp.register(">", |interp| {
+        let order = pop_order(interp)?;
+        interp
+            .stack
+            .push(Type::Bool(matches!(order, Some(Ordering::Greater))));
+        Ok(())
+    });
     // Greater than or equal
-    interp.dictionary.insert(
-        ">=".to_string(),
-        Word::Native(|interp| {
-            let (tos, _) = interp.pop_number()?;
-            let (nos, _) = interp.pop_number()?;
-            interp.stack.push(Type::Bool(nos >= tos));
-            Ok(())
-        }),
-    );
+    interp.register(">=", |interp| {
+        let order = pop_order(interp)?;
+        interp.stack.push(Type::Bool(matches!(
+            order,
+            Some(Ordering::Greater | Ordering::Equal)
+        )));
+        Ok(())
+    });
+
+    // n -- bool
+    interp.register("0=", |interp| {
+        let order = pop_sign(interp)?;
+        interp
+            .stack
+            .push(Type::Bool(order == Some(Ordering::Equal)));
+        Ok(())
+    });
+    interp.register("0<", |interp| {
+        let order = pop_sign(interp)?;
+        interp.stack.push(Type::Bool(order == Some(Ordering::Less)));
+        Ok(())
+    });
+
+    // a b -- -1, 0 or 1, in the order sort uses
+    interp.register("compare", |interp| {
+        let tos = interp.pop()?;
+        let nos = interp.pop()?;
+        let result = match nos.total_cmp(&tos) {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        };
+        interp.stack.push(Type::Int(result));
+        Ok(())
+    });
+
+    // The smaller or larger of two values, the first one if they are equal
+    interp.register("min", |interp| {
+        let tos = interp.pop()?;
+        let nos = interp.pop()?;
+        let min = if tos.total_cmp(&nos) == Ordering::Less {
+            tos
+        } else {
+            nos
+        };
+        interp.stack.push(min);
+        Ok(())
+    });
+    interp.register("max", |interp| {
+        let tos = interp.pop()?;
+        let nos = interp.pop()?;
+        let max = if tos.total_cmp(&nos) == Ordering::Greater {
+            tos
+        } else {
+            nos
+        };
+        interp.stack.push(max);
+        Ok(())
+    });
+
+    // x low high -- bool ; low <= x <= high
+    interp.register("between", |interp| {
+        let (x, low, high) = pop_range(interp)?;
+        let inside = matches!(order(&low, &x), Some(Ordering::Less | Ordering::Equal))
+            && matches!(order(&x, &high), Some(Ordering::Less | Ordering::Equal));
+        interp.stack.push(Type::Bool(inside));
+        Ok(())
+    });
+    // x low high -- bool ; low <= x < high
+    interp.register("within", |interp| {
+        let (x, low, high) = pop_range(interp)?;
+        let inside = matches!(order(&low, &x), Some(Ordering::Less | Ordering::Equal))
+            && matches!(order(&x, &high), Some(Ordering::Less));
+        interp.stack.push(Type::Bool(inside));
+        Ok(())
+    });
+}
+
+fn not_equal(interp: &mut Interpreter) -> Result<()> {
+    let tos = interp.pop()?;
+    let nos = interp.pop()?;
+    interp.stack.push(Type::Bool(!nos.equals(&tos)));
+    Ok(())
+}
+
+// Two numbers compare by value and NaN is unordered, anything else follows
+// the order between all values
+fn order(a: &Type, b: &Type) -> Option<Ordering> {
+    if a.is_number() && b.is_number() {
+        a.compare(b)
+    } else {
+        Some(a.total_cmp(b))
+    }
+}
+
+// Order of the second value against the top one
+fn pop_order(interp: &mut Interpreter) -> Result<Option<Ordering>> {
+    let tos = interp.pop()?;
+    let nos = interp.pop()?;
+    Ok(order(&nos, &tos))
+}
+
+// Order of a number against zero, None for NaN
+fn pop_sign(interp: &mut Interpreter) -> Result<Option<Ordering>> {
+    let value = interp.pop()?;
+    if !value.is_number() {
+        return Err(Error::type_mismatch("number", &value));
+    }
+    Ok(value.compare(&Type::Int(0)))
+}
+
+fn pop_range(interp: &mut Interpreter) -> Result<(Type, Type, Type)> {
+    let high = interp.pop()?;
+    let low = interp.pop()?;
+    let x = interp.pop()?;
+    Ok((x, low, high))
 }
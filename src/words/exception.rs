@@ -0,0 +1,11 @@
+use crate::error::ErrorKind;
+use crate::interpreter::Interpreter;
+
+pub fn register_exception_words(interp: &mut Interpreter) {
+    // Raise any value as an error, try ... catch gets it back
+    // value --
+    interp.register("throw", |interp| {
+        let value = interp.pop()?;
+        Err(ErrorKind::Thrown(value).into())
+    });
+}
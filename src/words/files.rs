@@ -0,0 +1,20 @@
+use crate::interpreter::Interpreter;
+
+// Words that read files, left out of sandboxed interpreters
+pub const FILE_WORDS: &[&str] = &["include", "require"];
+
+pub fn register_file_words(interp: &mut Interpreter) {
+    // Evaluate another file: "lib/util.sf" include
+    interp.register("include", |interp| {
+        let name = interp.pop_string()?;
+        let path = interp.resolve_include(&name)?;
+        interp.eval_file(path)
+    });
+
+    // Like include, but only the first time a file is required
+    interp.register("require", |interp| {
+        let name = interp.pop_string()?;
+        let path = interp.resolve_include(&name)?;
+        interp.require_file(path)
+    });
+}
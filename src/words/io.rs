@@ -1,28 +1,57 @@
-use crate::interpreter::{Interpreter, Word};
+use crate::bigint::BigInt;
+use crate::error::{Error, Result};
+use crate::interpreter::Interpreter;
 use crate::types::Type;
 
 pub fn register_io_words(interp: &mut Interpreter) {
     // Output and Consume the top-most value from the stack
-    interp.dictionary.insert(
-        ".".to_string(),
-        Word::Native(|interp| match interp.stack.pop() {
-            Some(Type::Int(n)) => {
-                println!("{}", n);
-                Ok(())
-            }
-            Some(Type::String(s)) => {
-                println!("{}", s);
-                Ok(())
-            }
-            Some(Type::Float(f)) => {
-                println!("{}", f);
-                Ok(())
-            }
-            Some(Type::Bool(b)) => {
-                println!("{}", b);
-                Ok(())
-            }
-            None => Err("Stack is empty!".to_string()),
-        }),
-    );
+    interp.register(".", |interp| {
+        let value = interp.pop()?;
+        let text = match value {
+            Type::Int(_) | Type::BigInt(_) => in_radix(&value, base(interp)?),
+            other => other.to_string(),
+        };
+        interp.write_output(&format!("{}\n", text))
+    });
+
+    // Print an int in hex or binary, whatever the base. Negative ints show
+    // their 64-bit two's complement, as the bit words see them.
+    interp.register(".hex", |interp| {
+        let text = match pop_integer(interp)? {
+            Type::Int(n) => format!("{:x}", n as u64),
+            value => in_radix(&value, 16),
+        };
+        interp.write_output(&format!("{}\n", text))
+    });
+    interp.register(".bin", |interp| {
+        let text = match pop_integer(interp)? {
+            Type::Int(n) => format!("{:b}", n as u64),
+            value => in_radix(&value, 2),
+        };
+        interp.write_output(&format!("{}\n", text))
+    });
+}
+
+// The radix in the base variable, 10 if it was removed
+fn base(interp: &Interpreter) -> Result<u32> {
+    match interp.get_var("base") {
+        None => Ok(10),
+        Some(Type::Int(n)) if (2..=36).contains(n) => Ok(*n as u32),
+        Some(other) => Err(Error::custom(format!("Invalid base {}", other.repr()))),
+    }
+}
+
+fn pop_integer(interp: &mut Interpreter) -> Result<Type> {
+    match interp.pop()? {
+        value @ (Type::Int(_) | Type::BigInt(_)) => Ok(value),
+        other => Err(Error::type_mismatch("int", &other)),
+    }
+}
+
+fn in_radix(value: &Type, radix: u32) -> String {
+    match value {
+        Type::Int(n) => BigInt::from(*n).to_string_radix(radix),
+        Type::BigInt(n) => n.to_string_radix(radix),
+        _ => unreachable!(),
+    }
 }
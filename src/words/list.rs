@@ -0,0 +1,127 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::interpreter::{Interpreter, Literal};
+use crate::types::Type;
+use crate::words::map::map_from_pairs;
+
+pub fn register_list_words(interp: &mut Interpreter) {
+    // Start a list literal: [ 1 2 3 ]
+    interp.register("[", |interp| {
+        interp
+            .literal_marks
+            .push((interp.stack.len(), Literal::List));
+        Ok(())
+    });
+
+    // Collect everything pushed since the matching [ or #[ into a list or map
+    interp.register("]", |interp| {
+        let (mark, literal) = interp
+            .literal_marks
+            .pop()
+            .ok_or_else(|| Error::syntax("']' without matching '['"))?;
+        if mark > interp.stack.len() {
+            return Err(ErrorKind::StackUnderflow.into());
+        }
+
+        let items = interp.split_stack(mark);
+        let value = match literal {
+            Literal::List => Type::List(items),
+            Literal::Map => Type::Map(map_from_pairs(items)?),
+        };
+        interp.stack.push(value);
+        Ok(())
+    });
+
+    // Item at an index, negative indexes count from the end
+    // list index -- item
+    interp.register("nth", |interp| {
+        let index = interp.pop_int()?;
+        let mut items = interp.pop_list()?;
+
+        let index = list_index(index, items.len())?;
+        interp.stack.push(items.swap_remove(index));
+        Ok(())
+    });
+
+    // Append to the end
+    // list item -- list
+    interp.register("push", |interp| {
+        let item = interp.pop()?;
+        let mut items = interp.pop_list()?;
+
+        items.push(item);
+        interp.stack.push(Type::List(items));
+        Ok(())
+    });
+
+    // Take the last item off
+    // list -- list item
+    interp.register("pop", |interp| {
+        let mut items = interp.pop_list()?;
+
+        let item = items
+            .pop()
+            .ok_or_else(|| Error::custom("Cannot pop from an empty list"))?;
+        interp.stack.push(Type::List(items));
+        interp.stack.push(item);
+        Ok(())
+    });
+
+    // Items from start up to but not including end
+    // list start end -- list
+    interp.register("slice", |interp| {
+        let end = interp.pop_int()?;
+        let start = interp.pop_int()?;
+        let mut items = interp.pop_list()?;
+
+        let end = slice_index(end, items.len())?;
+        let start = slice_index(start, items.len())?;
+        if start > end {
+            return Err(Error::custom(format!(
+                "Slice start {} is after its end {}",
+                start, end
+            )));
+        }
+
+        items.truncate(end);
+        items.drain(..start);
+        interp.stack.push(Type::List(items));
+        Ok(())
+    });
+
+    // Sort ascending, in the same order as <
+    interp.register("sort", |interp| {
+        let mut items = interp.pop_list()?;
+        items.sort_by(Type::total_cmp);
+        interp.stack.push(Type::List(items));
+        Ok(())
+    });
+
+    // Membership
+    // list item -- bool
+    interp.register("contains", |interp| {
+        let item = interp.pop()?;
+        let items = interp.pop_list()?;
+
+        let found = items.iter().any(|x| x.equals(&item));
+        interp.stack.push(Type::Bool(found));
+        Ok(())
+    });
+}
+
+// Turn a possibly negative index into a position in a list of `len` items
+fn list_index(index: i64, len: usize) -> Result<usize> {
+    let position = if index < 0 { len as i64 + index } else { index };
+    if position < 0 || position >= len as i64 {
+        return Err(ErrorKind::IndexOutOfRange { index, len }.into());
+    }
+    Ok(position as usize)
+}
+
+// Like `list_index`, but may also point one past the last item
+fn slice_index(index: i64, len: usize) -> Result<usize> {
+    if index == len as i64 {
+        Ok(len)
+    } else {
+        list_index(index, len)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::interpreter::{Interpreter, Literal};
+use crate::types::Type;
+use std::collections::BTreeMap;
+
+pub fn register_map_words(interp: &mut Interpreter) {
+    // Start a map literal of keys and values: #[ "name" "smorth" "version" 1 ]
+    interp.register("#[", |interp| {
+        interp
+            .literal_marks
+            .push((interp.stack.len(), Literal::Map));
+        Ok(())
+    });
+
+    // Value stored under a key
+    // map key -- value
+    interp.register("get", |interp| {
+        let key = interp.pop_string()?;
+        let mut map = interp.pop_map()?;
+
+        let value = map.remove(&key).ok_or(ErrorKind::MissingKey(key))?;
+        interp.stack.push(value);
+        Ok(())
+    });
+
+    // Store a value under a key, replacing any previous one
+    // map key value -- map
+    interp.register("set", |interp| {
+        let value = interp.pop()?;
+        let key = interp.pop_string()?;
+        let mut map = interp.pop_map()?;
+
+        map.insert(key, value);
+        interp.stack.push(Type::Map(map));
+        Ok(())
+    });
+
+    // Remove a key if it is there
+    // map key -- map
+    interp.register("delete", |interp| {
+        let key = interp.pop_string()?;
+        let mut map = interp.pop_map()?;
+
+        map.remove(&key);
+        interp.stack.push(Type::Map(map));
+        Ok(())
+    });
+
+    // map key -- bool
+    interp.register("has-key?", |interp| {
+        let key = interp.pop_string()?;
+        let map = interp.pop_map()?;
+
+        interp.stack.push(Type::Bool(map.contains_key(&key)));
+        Ok(())
+    });
+
+    // Sorted list of the keys
+    // map -- list
+    interp.register("keys", |interp| {
+        let map = interp.pop_map()?;
+
+        let keys = map.into_keys().map(Type::String).collect();
+        interp.stack.push(Type::List(keys));
+        Ok(())
+    });
+
+    // Values in the order of their keys
+    // map -- list
+    interp.register("values", |interp| {
+        let map = interp.pop_map()?;
+
+        let values = map.into_values().collect();
+        interp.stack.push(Type::List(values));
+        Ok(())
+    });
+}
+
+// Build a map from alternating keys and values
+pub(crate) fn map_from_pairs(items: Vec<Type>) -> Result<BTreeMap<String, Type>> {
+    if !items.len().is_multiple_of(2) {
+        return Err(Error::custom("Map literal needs a value for every key"));
+    }
+
+    let mut map = BTreeMap::new();
+    let mut items = items.into_iter();
+    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+        match key {
+            Type::String(key) => map.insert(key, value),
+            other => return Err(Error::type_mismatch("string key", &other)),
+        };
+    }
+    Ok(map)
+}
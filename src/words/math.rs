@@ -1,34 +1,328 @@
-use crate::interpreter::{Interpreter, Word};
+use crate::bigint::BigInt;
+use crate::decimal::{Decimal, Rounding};
+use crate::error::{Error, Result};
+use crate::interpreter::Interpreter;
+use crate::number::{self, Arith, Division};
+use crate::rational::Rational;
 use crate::types::Type;
+use std::cmp::Ordering;
 
 pub fn register_math_words(interp: &mut Interpreter) {
     // Math Operations
-    interp.dictionary.insert(
-        "+".to_string(),
-        Word::Native(|interp| interp.binary_op(|a, b| a + b)),
-    );
-
-    interp.dictionary.insert(
-        "-".to_string(),
-        Word::Native(|interp| interp.binary_op(|a, b| a - b)),
-    );
-
-    interp.dictionary.insert(
-        "*".to_string(),
-        Word::Native(|interp| interp.binary_op(|a, b| a * b)),
-    );
-    interp.dictionary.insert(
-        "/".to_string(),
-        Word::Native(|interp| interp.binary_op(|a, b| a / b)),
-    );
+    interp.register("+", |interp| interp.binary_op(Arith::Add));
+
+    interp.register("-", |interp| interp.binary_op(Arith::Sub));
+
+    interp.register("*", |interp| interp.binary_op(Arith::Mul));
+    // Integer division rounds towards zero, or down after floored-division
+    interp.register("/", |interp| interp.binary_op(Arith::Div));
+    // Remainder of /
+    interp.register("mod", |interp| interp.binary_op(Arith::Mod));
+    // Remainder with the sign of the dividend, whichever way / rounds
+    interp.register("rem", |interp| interp.binary_op(Arith::Rem));
+
+    // a b -- remainder quotient
+    interp.register("/mod", |interp| {
+        let tos = interp.pop()?;
+        let nos = interp.pop()?;
+        let division = interp.numbers.division;
+        let (quotient, remainder) = number::div_mod(&nos, &tos, division, &interp.numbers)?;
+        interp.stack.push(remainder);
+        interp.stack.push(quotient);
+        Ok(())
+    });
+
+    // Division that always gives a float
+    interp.register("f/", |interp| {
+        let tos = interp.pop_float()?;
+        let nos = interp.pop_float()?;
+        interp.stack.push(Type::Float(nos / tos));
+        Ok(())
+    });
+
+    // Choose which way integer division rounds
+    interp.register("floored-division", |interp| {
+        interp.set_division(Division::Floor);
+        Ok(())
+    });
+    interp.register("truncated-division", |interp| {
+        interp.set_division(Division::Truncate);
+        Ok(())
+    });
+    // base exponent -- power
+    interp.register("pow", |interp| interp.binary_op(Arith::Pow));
+
+    // Parts of a rational, an int is its own numerator over 1
+    interp.register("numerator", |interp| {
+        let numerator = match interp.pop()? {
+            Type::Rational(r) => Type::from(r.numerator().clone()),
+            n @ (Type::Int(_) | Type::BigInt(_)) => n,
+            other => return Err(Error::type_mismatch("rational", &other)),
+        };
+        interp.stack.push(numerator);
+        Ok(())
+    });
+    interp.register("denominator", |interp| {
+        let denominator = match interp.pop()? {
+            Type::Rational(r) => Type::from(r.denominator().clone()),
+            Type::Int(_) | Type::BigInt(_) => Type::Int(1),
+            other => return Err(Error::type_mismatch("rational", &other)),
+        };
+        interp.stack.push(denominator);
+        Ok(())
+    });
+
+    // Any number as a float
+    interp.register(">float", |interp| {
+        let (num, _) = interp.pop_number()?;
+        interp.stack.push(Type::Float(num));
+        Ok(())
+    });
+
+    // Any number as a decimal, fractions are rounded to the decimal scale
+    interp.register(">decimal", |interp| {
+        let decimal = match interp.pop()? {
+            Type::Int(n) => Decimal::from(BigInt::from(n)),
+            Type::BigInt(n) => Decimal::from(n),
+            Type::Decimal(d) => d,
+            Type::Rational(r) => {
+                let numbers = &interp.numbers;
+                Decimal::from_rational(&r, numbers.decimal_scale, numbers.rounding)
+            }
+            // The shortest digits that read back as the same float, 0.1 is 0.1
+            Type::Float(f) => Decimal::parse(&f.to_string())
+                .ok_or_else(|| Error::custom(format!("{} has no decimal value", f)))?,
+            other => return Err(Error::type_mismatch("number", &other)),
+        };
+        interp.stack.push(Type::Decimal(decimal));
+        Ok(())
+    });
+
+    // n -- ; most digits after the point decimal results keep
+    interp.register("decimal-scale", |interp| {
+        let scale = interp.pop_int()?;
+        let scale = number::decimal_scale(scale)?;
+        interp.set_decimal_scale(scale)
+    });
+    // "half-up" rounding, or half-even, floor, ceiling
+    interp.register("rounding", |interp| {
+        let name = interp.pop_string()?;
+        let rounding = Rounding::from_name(&name)
+            .ok_or_else(|| Error::custom(format!("Unknown rounding mode '{}'", name)))?;
+        interp.set_rounding(rounding);
+        Ok(())
+    });
+
+    // Whether literals like 0.1 are decimals or floats
+    interp.register("decimal-literals", |interp| {
+        interp.set_decimal_literals(true);
+        Ok(())
+    });
+    interp.register("float-literals", |interp| {
+        interp.set_decimal_literals(false);
+        Ok(())
+    });
+
+    // Towards zero, exactly for every kind of number
+    interp.register(">int", |interp| {
+        let int = match interp.pop()? {
+            n @ (Type::Int(_) | Type::BigInt(_)) => n,
+            Type::Rational(r) => Type::from(r.trunc()),
+            Type::Decimal(d) => {
+                let rounding = if d.is_negative() {
+                    Rounding::Ceiling
+                } else {
+                    Rounding::Floor
+                };
+                Type::from(d.round(0, rounding).to_rational().numerator().clone())
+            }
+            Type::Float(f) => float_to_int(f)?,
+            other => return Err(Error::type_mismatch("number", &other)),
+        };
+        interp.stack.push(int);
+        Ok(())
+    });
+
+    // Sign changes keep the kind of number, ints grow into big integers
+    interp.register("negate", |interp| {
+        let value = interp.pop()?;
+        let negated = negate(interp, value)?;
+        interp.stack.push(negated);
+        Ok(())
+    });
+    interp.register("abs", |interp| {
+        let value = interp.pop()?;
+        let abs = if value.compare(&Type::Int(0)) == Some(Ordering::Less) {
+            negate(interp, value)?
+        } else {
+            value
+        };
+        interp.stack.push(abs);
+        Ok(())
+    });
+
+    // Whole numbers. Ints stay as they are, floats give whole floats and
+    // decimals keep no digits after the point.
+    interp.register("floor", |interp| round_whole(interp, Whole::Floor));
+    interp.register("ceil", |interp| round_whole(interp, Whole::Ceil));
+    interp.register("trunc", |interp| round_whole(interp, Whole::Trunc));
+    // Halves away from zero, decimals by the rounding mode
+    interp.register("round", |interp| round_whole(interp, Whole::Round));
+    // What trunc takes away, with the sign of the number
+    interp.register("fract", |interp| {
+        let value = interp.pop()?;
+        let fract = match value {
+            Type::Float(f) => Type::Float(f.fract()),
+            value => {
+                let whole = whole(interp, value.clone(), Whole::Trunc)?;
+                number::arith(Arith::Sub, &value, &whole, &interp.numbers)?
+            }
+        };
+        interp.stack.push(fract);
+        Ok(())
+    });
 
     // Takes the value off the top of the stack and sqrts it
-    interp.dictionary.insert(
-        "sqrt".to_string(),
-        Word::Native(|interp| {
-            let (num, _) = interp.pop_number()?;
-            interp.stack.push(Type::Float(num.sqrt()));
-            Ok(())
+    interp.register("sqrt", |interp| float_fn(interp, f64::sqrt));
+
+    // Exponentials and logarithms, always floats
+    interp.register("exp", |interp| float_fn(interp, f64::exp));
+    interp.register("ln", |interp| float_fn(interp, f64::ln));
+    interp.register("log10", |interp| float_fn(interp, f64::log10));
+    interp.register("log2", |interp| float_fn(interp, f64::log2));
+
+    // Trigonometry in radians
+    interp.register("sin", |interp| float_fn(interp, f64::sin));
+    interp.register("cos", |interp| float_fn(interp, f64::cos));
+    interp.register("tan", |interp| float_fn(interp, f64::tan));
+    interp.register("asin", |interp| float_fn(interp, f64::asin));
+    interp.register("acos", |interp| float_fn(interp, f64::acos));
+    interp.register("atan", |interp| float_fn(interp, f64::atan));
+    // y x -- angle
+    interp.register("atan2", |interp| {
+        let x = interp.pop_float()?;
+        let y = interp.pop_float()?;
+        interp.stack.push(Type::Float(y.atan2(x)));
+        Ok(())
+    });
+    // a b -- sqrt(a*a + b*b)
+    interp.register("hypot", |interp| {
+        let b = interp.pop_float()?;
+        let a = interp.pop_float()?;
+        interp.stack.push(Type::Float(a.hypot(b)));
+        Ok(())
+    });
+
+    // Constants
+    interp.register("pi", |interp| {
+        interp.stack.push(Type::Float(std::f64::consts::PI));
+        Ok(())
+    });
+    interp.register("e", |interp| {
+        interp.stack.push(Type::Float(std::f64::consts::E));
+        Ok(())
+    });
+    interp.register("inf", |interp| {
+        interp.stack.push(Type::Float(f64::INFINITY));
+        Ok(())
+    });
+    interp.register("nan", |interp| {
+        interp.stack.push(Type::Float(f64::NAN));
+        Ok(())
+    });
+    // Only floats can be NaN
+    interp.register("nan?", |interp| {
+        let nan = match interp.pop()? {
+            Type::Float(f) => f.is_nan(),
+            n if n.is_number() => false,
+            other => return Err(Error::type_mismatch("number", &other)),
+        };
+        interp.stack.push(Type::Bool(nan));
+        Ok(())
+    });
+}
+
+fn float_fn(interp: &mut Interpreter, f: fn(f64) -> f64) -> Result<()> {
+    let (num, _) = interp.pop_number()?;
+    interp.stack.push(Type::Float(f(num)));
+    Ok(())
+}
+
+fn negate(interp: &Interpreter, value: Type) -> Result<Type> {
+    match value {
+        // 0 - x would give 0.0 instead of -0.0
+        Type::Float(f) => Ok(Type::Float(-f)),
+        value => number::arith(Arith::Sub, &Type::Int(0), &value, &interp.numbers),
+    }
+}
+
+// Which way a number is made whole
+#[derive(Clone, Copy)]
+enum Whole {
+    Floor,
+    Ceil,
+    Trunc,
+    Round,
+}
+
+fn round_whole(interp: &mut Interpreter, mode: Whole) -> Result<()> {
+    let value = interp.pop()?;
+    let result = whole(interp, value, mode)?;
+    interp.stack.push(result);
+    Ok(())
+}
+
+fn whole(interp: &Interpreter, value: Type, mode: Whole) -> Result<Type> {
+    Ok(match value {
+        Type::Int(_) | Type::BigInt(_) => value,
+        Type::Float(f) => Type::Float(match mode {
+            Whole::Floor => f.floor(),
+            Whole::Ceil => f.ceil(),
+            Whole::Trunc => f.trunc(),
+            Whole::Round => f.round(),
         }),
-    );
+        Type::Rational(r) => {
+            let half = Rational::new(BigInt::from(1), BigInt::from(2)).unwrap();
+            let negative = r.numerator().is_negative();
+            Type::from(match mode {
+                Whole::Floor => r.floor(),
+                Whole::Ceil => r.neg().floor().neg(),
+                Whole::Trunc => r.trunc(),
+                Whole::Round if negative => r.neg().add(&half).floor().neg(),
+                Whole::Round => r.add(&half).floor(),
+            })
+        }
+        Type::Decimal(d) => {
+            let rounding = match mode {
+                Whole::Floor => Rounding::Floor,
+                Whole::Ceil => Rounding::Ceiling,
+                Whole::Trunc if d.is_negative() => Rounding::Ceiling,
+                Whole::Trunc => Rounding::Floor,
+                Whole::Round => interp.numbers.rounding,
+            };
+            Type::Decimal(d.round(0, rounding))
+        }
+        other => return Err(Error::type_mismatch("number", &other)),
+    })
+}
+
+// The whole part of a float as an exact int
+fn float_to_int(f: f64) -> Result<Type> {
+    if !f.is_finite() {
+        return Err(Error::custom(format!("Cannot convert {} to an int", f)));
+    }
+    let f = f.trunc();
+    if f.abs() < 9.0e18 {
+        return Ok(Type::Int(f as i64));
+    }
+    // Floats this large are their 53 bit mantissa times a power of two
+    let bits = f.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as u32;
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let magnitude = BigInt::from(mantissa as i64).mul(&BigInt::from(2).pow(exponent - 1075));
+    Ok(Type::from(if f < 0.0 {
+        magnitude.neg()
+    } else {
+        magnitude
+    }))
 }
@@ -1,14 +1,30 @@
+pub mod bitwise;
+pub mod combinators;
 pub mod comparison;
+pub mod exception;
+pub mod files;
 pub mod io;
+pub mod list;
 pub mod logic;
+pub mod map;
 pub mod math;
+pub mod random;
 pub mod stack;
+pub mod stats;
 pub mod stringwords;
 
 // Re-export the register functions for convenience
+pub use bitwise::register_bitwise_words;
+pub use combinators::register_combinator_words;
 pub use comparison::register_comparison_words;
+pub use exception::register_exception_words;
+pub use files::{FILE_WORDS, register_file_words};
 pub use io::register_io_words;
+pub use list::register_list_words;
 pub use logic::register_logic_words;
+pub use map::register_map_words;
 pub use math::register_math_words;
+pub use random::register_random_words;
 pub use stack::register_stack_words;
+pub use stats::register_stats_words;
 pub use stringwords::register_string_words;
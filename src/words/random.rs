@@ -0,0 +1,57 @@
+use crate::error::Error;
+use crate::interpreter::Interpreter;
+use crate::random::Rng;
+use crate::types::Type;
+
+pub fn register_random_words(interp: &mut Interpreter) {
+    // -- f ; uniform in [0, 1)
+    interp.register("random", |interp| {
+        let f = interp.rng.next_f64();
+        interp.stack.push(Type::Float(f));
+        Ok(())
+    });
+
+    // low high -- n ; uniform from low to high, both included
+    interp.register("random-int", |interp| {
+        let high = interp.pop_int()?;
+        let low = interp.pop_int()?;
+        if low > high {
+            let message = format!("Empty range from {} to {}", low, high);
+            return Err(Error::custom(message));
+        }
+        // The span wraps to 0 only for the full i64 range
+        let span = (high.wrapping_sub(low) as u64).wrapping_add(1);
+        let n = low.wrapping_add(interp.rng.below(span) as i64);
+        interp.stack.push(Type::Int(n));
+        Ok(())
+    });
+
+    // list -- list ; the same items in random order
+    interp.register("shuffle", |interp| {
+        let mut items = interp.pop_list()?;
+        for i in (1..items.len()).rev() {
+            let j = interp.rng.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+        interp.stack.push(Type::List(items));
+        Ok(())
+    });
+
+    // list -- item
+    interp.register("choice", |interp| {
+        let mut items = interp.pop_list()?;
+        if items.is_empty() {
+            return Err(Error::custom("Cannot choose from an empty list"));
+        }
+        let i = interp.rng.below(items.len() as u64) as usize;
+        interp.stack.push(items.swap_remove(i));
+        Ok(())
+    });
+
+    // n -- ; restart the sequence, the same seed gives the same numbers
+    interp.register("seed", |interp| {
+        let seed = interp.pop_int()?;
+        interp.rng = Rng::new(seed as u64);
+        Ok(())
+    });
+}
@@ -0,0 +1,266 @@
+use crate::error::{Error, Result};
+use crate::interpreter::Interpreter;
+use crate::number::{self, Arith};
+use crate::types::Type;
+use std::cmp::Ordering;
+
+// Most buckets a histogram can have
+const MAX_BUCKETS: i64 = 1 << 20;
+
+// Summaries of lists of numbers. Ints, rationals and decimals stay exact, so
+// the mean of [ 1 2 ] is 3/2.
+pub fn register_stats_words(interp: &mut Interpreter) {
+    interp.register("sum", |interp| {
+        let items = pop_numbers(interp)?;
+        let sum = sum(interp, &items)?;
+        interp.stack.push(sum);
+        Ok(())
+    });
+    interp.register("product", |interp| {
+        let items = pop_numbers(interp)?;
+        let mut product = Type::Int(1);
+        for item in &items {
+            product = arith(interp, Arith::Mul, &product, item)?;
+        }
+        interp.stack.push(product);
+        Ok(())
+    });
+
+    interp.register("mean", |interp| {
+        let items = pop_sample(interp, 1)?;
+        let mean = mean(interp, &items)?;
+        interp.stack.push(mean);
+        Ok(())
+    });
+    // The middle value, or the mean of the two middle ones
+    interp.register("median", |interp| {
+        let mut items = pop_sample(interp, 1)?;
+        items.sort_by(Type::total_cmp);
+        let middle = items.len() / 2;
+        let median = if items.len() % 2 == 1 {
+            items.swap_remove(middle)
+        } else {
+            mean(interp, &items[middle - 1..=middle])?
+        };
+        interp.stack.push(median);
+        Ok(())
+    });
+    // The most common value, the smallest of them on a tie
+    interp.register("mode", |interp| {
+        let mut items = pop_sample(interp, 1)?;
+        items.sort_by(Type::total_cmp);
+        let mut best = (0, 0);
+        let mut start = 0;
+        for i in 1..=items.len() {
+            if i == items.len() || !items[i].equals(&items[start]) {
+                if i - start > best.1 {
+                    best = (start, i - start);
+                }
+                start = i;
+            }
+        }
+        interp.stack.push(items.swap_remove(best.0));
+        Ok(())
+    });
+
+    // Sample variance, dividing by one less than the number of values
+    interp.register("variance", |interp| {
+        let items = pop_sample(interp, 2)?;
+        let variance = variance(interp, &items)?;
+        interp.stack.push(variance);
+        Ok(())
+    });
+    interp.register("stddev", |interp| {
+        let items = pop_sample(interp, 2)?;
+        let variance = variance(interp, &items)?;
+        interp
+            .stack
+            .push(Type::Float(number::to_float(&variance)?.sqrt()));
+        Ok(())
+    });
+
+    // list p -- value ; p from 0 to 100, between two values it interpolates
+    interp.register("percentile", |interp| {
+        let p = interp.pop()?;
+        let mut items = pop_sample(interp, 1)?;
+        // False for NaN and anything that isn't a number
+        let in_range = matches!(
+            p.compare(&Type::Int(0)),
+            Some(Ordering::Greater | Ordering::Equal)
+        ) && matches!(
+            p.compare(&Type::Int(100)),
+            Some(Ordering::Less | Ordering::Equal)
+        );
+        if !in_range {
+            let message = format!("Percentile {} is not between 0 and 100", p.repr());
+            return Err(Error::custom(message));
+        }
+        items.sort_by(Type::total_cmp);
+
+        // Position in the sorted values, counting from 0
+        let last = Type::Int(items.len() as i64 - 1);
+        let position = arith(interp, Arith::Mul, &p, &last)?;
+        let position = number::ratio(&position, &Type::Int(100), &interp.numbers)?;
+        let below = number::to_float(&position)?.floor() as usize;
+        let fraction = arith(interp, Arith::Sub, &position, &Type::Int(below as i64))?;
+
+        let low = &items[below];
+        let value = match items.get(below + 1) {
+            Some(high) if !fraction.equals(&Type::Int(0)) => {
+                let gap = arith(interp, Arith::Sub, high, low)?;
+                let offset = arith(interp, Arith::Mul, &gap, &fraction)?;
+                arith(interp, Arith::Add, low, &offset)?
+            }
+            _ => low.clone(),
+        };
+        interp.stack.push(value);
+        Ok(())
+    });
+
+    // Smallest and largest values, in the order of <
+    interp.register("min-of", |interp| {
+        let items = pop_sample(interp, 1)?;
+        let min = items.into_iter().reduce(|min, item| {
+            if item.total_cmp(&min) == Ordering::Less {
+                item
+            } else {
+                min
+            }
+        });
+        interp.stack.push(min.unwrap());
+        Ok(())
+    });
+    interp.register("max-of", |interp| {
+        let items = pop_sample(interp, 1)?;
+        let max = items.into_iter().reduce(|max, item| {
+            if item.total_cmp(&max) == Ordering::Greater {
+                item
+            } else {
+                max
+            }
+        });
+        interp.stack.push(max.unwrap());
+        Ok(())
+    });
+
+    // list n -- counts ; how many values fall in each of n equal buckets
+    // from the smallest value to the largest
+    interp.register("histogram", |interp| {
+        let buckets = interp.pop_int()?;
+        let items = pop_numbers(interp)?;
+        if !(1..=MAX_BUCKETS).contains(&buckets) {
+            let message = format!(
+                "Bucket count {} is not between 1 and {}",
+                buckets, MAX_BUCKETS
+            );
+            return Err(Error::custom(message));
+        }
+        let buckets = buckets as usize;
+        interp.reserve(buckets * std::mem::size_of::<Type>())?;
+
+        let values = items
+            .iter()
+            .map(number::to_float)
+            .collect::<Result<Vec<f64>>>()?;
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mut counts = vec![0i64; buckets];
+        for value in values {
+            let bucket = if max > min {
+                ((value - min) / (max - min) * buckets as f64) as usize
+            } else {
+                0
+            };
+            // The largest value belongs to the last bucket
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        interp
+            .stack
+            .push(Type::List(counts.into_iter().map(Type::Int).collect()));
+        Ok(())
+    });
+
+    // xs ys -- slope intercept ; least squares line through the points
+    interp.register("linreg", |interp| {
+        let ys = pop_sample(interp, 2)?;
+        let xs = pop_sample(interp, 2)?;
+        if xs.len() != ys.len() {
+            let message = format!(
+                "linreg needs as many xs as ys, got {} and {}",
+                xs.len(),
+                ys.len()
+            );
+            return Err(Error::custom(message));
+        }
+
+        let mean_x = mean(interp, &xs)?;
+        let mean_y = mean(interp, &ys)?;
+        let mut covariance = Type::Int(0);
+        let mut spread = Type::Int(0);
+        for (x, y) in xs.iter().zip(&ys) {
+            let dx = arith(interp, Arith::Sub, x, &mean_x)?;
+            let dy = arith(interp, Arith::Sub, y, &mean_y)?;
+            let product = arith(interp, Arith::Mul, &dx, &dy)?;
+            covariance = arith(interp, Arith::Add, &covariance, &product)?;
+            let square = arith(interp, Arith::Mul, &dx, &dx)?;
+            spread = arith(interp, Arith::Add, &spread, &square)?;
+        }
+        if spread.equals(&Type::Int(0)) {
+            return Err(Error::custom("linreg needs at least two different xs"));
+        }
+
+        let slope = number::ratio(&covariance, &spread, &interp.numbers)?;
+        let rise = arith(interp, Arith::Mul, &slope, &mean_x)?;
+        let intercept = arith(interp, Arith::Sub, &mean_y, &rise)?;
+        interp.stack.push(slope);
+        interp.stack.push(intercept);
+        Ok(())
+    });
+}
+
+fn arith(interp: &Interpreter, op: Arith, a: &Type, b: &Type) -> Result<Type> {
+    number::arith(op, a, b, &interp.numbers)
+}
+
+fn sum(interp: &Interpreter, items: &[Type]) -> Result<Type> {
+    let mut sum = Type::Int(0);
+    for item in items {
+        sum = arith(interp, Arith::Add, &sum, item)?;
+    }
+    Ok(sum)
+}
+
+fn mean(interp: &Interpreter, items: &[Type]) -> Result<Type> {
+    let sum = sum(interp, items)?;
+    number::ratio(&sum, &Type::Int(items.len() as i64), &interp.numbers)
+}
+
+fn variance(interp: &Interpreter, items: &[Type]) -> Result<Type> {
+    let mean = mean(interp, items)?;
+    let mut squares = Type::Int(0);
+    for item in items {
+        let deviation = arith(interp, Arith::Sub, item, &mean)?;
+        let square = arith(interp, Arith::Mul, &deviation, &deviation)?;
+        squares = arith(interp, Arith::Add, &squares, &square)?;
+    }
+    let count = Type::Int(items.len() as i64 - 1);
+    number::ratio(&squares, &count, &interp.numbers)
+}
+
+fn pop_numbers(interp: &mut Interpreter) -> Result<Vec<Type>> {
+    let items = interp.pop_list()?;
+    if let Some(item) = items.iter().find(|item| !item.is_number()) {
+        return Err(Error::type_mismatch("number", item));
+    }
+    Ok(items)
+}
+
+// A list of numbers with at least `min` of them
+fn pop_sample(interp: &mut Interpreter, min: usize) -> Result<Vec<Type>> {
+    let items = pop_numbers(interp)?;
+    if items.len() < min {
+        let message = format!("Need at least {} values, got {}", min, items.len());
+        return Err(Error::custom(message));
+    }
+    Ok(items)
+}
@@ -1,126 +1,148 @@
-use crate::interpreter::{Interpreter, Word};
+use crate::bigint::BigInt;
+use crate::decimal::Decimal;
+use crate::error::{Error, ErrorKind, Result};
+use crate::interpreter::Interpreter;
+use crate::number;
 use crate::types::Type;
 
 pub fn register_string_words(interp: &mut Interpreter) {
-    // String concatenation
-    interp.dictionary.insert(
-        "..".to_string(),
-        Word::Native(|interp| {
-            let tos = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-            let nos = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-
-            interp.stack.push(Type::String(format!("{}{}", nos, tos)));
-            Ok(())
-        }),
-    );
-
-    // String length
-    interp.dictionary.insert(
-        "len".to_string(),
-        Word::Native(|interp| {
-            let tos = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-
-            interp.stack.push(Type::Int(tos.len() as i64));
-            Ok(())
-        }),
-    );
-
-    // Reverse string
-    interp.dictionary.insert(
-        "reverse".to_string(),
-        Word::Native(|interp| {
-            let tos = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-
-            let reversed: String = tos.chars().rev().collect();
-            interp.stack.push(Type::String(reversed));
-            Ok(())
-        }),
-    );
+    // String or list concatenation
+    interp.register("..", |interp| {
+        let tos = interp.pop()?;
+        let nos = interp.pop()?;
+
+        // The result is a new copy, refuse it before making it
+        interp.reserve(nos.size() + tos.size())?;
+        let joined = match (nos, tos) {
+            (Type::String(a), Type::String(b)) => Type::String(format!("{}{}", a, b)),
+            (Type::List(mut a), Type::List(b)) => {
+                a.extend(b);
+                Type::List(a)
+            }
+            (Type::List(_), other) => return Err(Error::type_mismatch("list", &other)),
+            (_, other) => return Err(Error::type_mismatch("string", &other)),
+        };
+        interp.stack.push(joined);
+        Ok(())
+    });
+
+    // String, list or map length
+    interp.register("len", |interp| {
+        let len = match interp.pop()? {
+            Type::String(s) => s.len(),
+            Type::List(items) => items.len(),
+            Type::Map(map) => map.len(),
+            other => return Err(Error::type_mismatch("string, list or map", &other)),
+        };
+
+        interp.stack.push(Type::Int(len as i64));
+        Ok(())
+    });
+
+    // Reverse string or list
+    interp.register("reverse", |interp| {
+        let reversed = match interp.pop()? {
+            Type::String(s) => Type::String(s.chars().rev().collect()),
+            Type::List(mut items) => {
+                items.reverse();
+                Type::List(items)
+            }
+            other => return Err(Error::type_mismatch("string or list", &other)),
+        };
+        interp.stack.push(reversed);
+        Ok(())
+    });
 
     // Uppercase string
-    interp.dictionary.insert(
-        "upper".to_string(),
-        Word::Native(|interp| {
-            let tos = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-
-            interp.stack.push(Type::String(tos.to_uppercase()));
-            Ok(())
-        }),
-    );
+    interp.register("upper", |interp| {
+        let tos = interp.pop_string()?;
+
+        interp.stack.push(Type::String(tos.to_uppercase()));
+        Ok(())
+    });
     // Lowercase string
-    interp.dictionary.insert(
-        "lower".to_string(),
-        Word::Native(|interp| {
-            let tos = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-
-            interp.stack.push(Type::String(tos.to_lowercase()));
-            Ok(())
-        }),
-    );
-
-    // String interpolation
-    interp.dictionary.insert(
-        "format".to_string(),
-        Word::Native(|interp| {
-            // Pop the format string
-            let fmt = match interp.stack.pop() {
-                Some(Type::String(s)) => s,
-                Some(_) => return Err("Expected format string".to_string()),
-                None => return Err("Stack underflow!".to_string()),
-            };
-
-            // Count placeholders
-            let placeholder_count = fmt.matches("$").count();
-
-            // Pop values (in reverse since stack is LIFO)
-            let mut values = Vec::new();
-            for _ in 0..placeholder_count {
-                let val = interp
-                    .stack
-                    .pop()
-                    .ok_or("Not enough values for format string")?;
-                values.push(val);
+    interp.register("lower", |interp| {
+        let tos = interp.pop_string()?;
+
+        interp.stack.push(Type::String(tos.to_lowercase()));
+        Ok(())
+    });
+
+    // String interpolation, "$ costs $.2" fills each $ with a value and
+    // $.N shows a number with N digits after the point
+    interp.register("format", |interp| {
+        let fmt = interp.pop_string()?;
+        let pieces = parse_format(&fmt);
+
+        let count = pieces
+            .iter()
+            .filter(|piece| matches!(piece, Piece::Value(_)))
+            .count();
+        if interp.stack.len() < count {
+            return Err(ErrorKind::StackUnderflow.into());
+        }
+        let values = interp.split_stack(interp.stack.len() - count);
+
+        let mut result = String::new();
+        let mut values = values.into_iter();
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => result.push_str(text),
+                Piece::Value(None) => result.push_str(&values.next().unwrap().to_string()),
+                Piece::Value(Some(precision)) => {
+                    let precision = number::decimal_scale(precision)?;
+                    let value = values.next().unwrap();
+                    result.push_str(&with_precision(interp, &value, precision)?);
+                }
             }
-            values.reverse(); // Reverse to get correct order
-
-            // Build the result string
-            let mut result = fmt.clone();
-            for val in values {
-                let val_str = match val {
-                    Type::Int(n) => n.to_string(),
-                    Type::Float(f) => f.to_string(),
-                    Type::String(s) => s,
-                    Type::Bool(b) => b.to_string(),
-                };
-                result = result.replacen("$", &val_str, 1);
+        }
+
+        interp.stack.push(Type::String(result));
+        Ok(())
+    });
+}
+
+enum Piece<'a> {
+    Text(&'a str),
+    // A placeholder, with the digits wanted after the point
+    Value(Option<i64>),
+}
+
+fn parse_format(fmt: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = fmt;
+    while let Some(start) = rest.find('$') {
+        pieces.push(Piece::Text(&rest[..start]));
+        rest = &rest[start + 1..];
+
+        let digits = rest.strip_prefix('.').map(|after| {
+            let end = after
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(after.len());
+            &after[..end]
+        });
+        match digits.and_then(|digits| Some((digits, digits.parse::<i64>().ok()?))) {
+            Some((digits, precision)) => {
+                pieces.push(Piece::Value(Some(precision)));
+                rest = &rest[1 + digits.len()..];
             }
+            None => pieces.push(Piece::Value(None)),
+        }
+    }
+    pieces.push(Piece::Text(rest));
+    pieces
+}
 
-            interp.stack.push(Type::String(result));
-            Ok(())
-        }),
-    );
+// A number with exactly `precision` digits after the point
+fn with_precision(interp: &Interpreter, value: &Type, precision: u32) -> Result<String> {
+    let rounding = interp.numbers.rounding;
+    let decimal = match value {
+        Type::Float(f) => return Ok(format!("{:.*}", precision as usize, f)),
+        Type::Int(n) => Decimal::from(BigInt::from(*n)),
+        Type::BigInt(n) => Decimal::from(n.clone()),
+        Type::Rational(r) => Decimal::from_rational(r, precision, rounding),
+        Type::Decimal(d) => d.clone(),
+        other => return Err(Error::type_mismatch("number", other)),
+    };
+    Ok(decimal.with_scale(precision, rounding).to_string())
 }
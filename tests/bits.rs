@@ -0,0 +1,75 @@
+use smorth::{ErrorKind, Interpreter, Type};
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+fn run(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+// Output shared with the test, which can't get it back out of the interpreter
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn printed(src: &str) -> String {
+    let output = Output::default();
+    let mut interp = Interpreter::new();
+    interp.set_output(output.clone());
+    interp.eval(src).unwrap();
+    String::from_utf8(output.0.take()).unwrap()
+}
+
+#[test]
+fn integer_literals() {
+    assert_eq!(run("0x1F 0b1010 0o17 1_000_000"), "31 10 15 1000000");
+    assert_eq!(run("-0x10 0xFFFFFFFFFFFFFFFF"), "-16 18446744073709551615");
+}
+
+#[test]
+fn bit_words() {
+    assert_eq!(
+        run("0x1F 0b0100 bor 6 3 band 6 3 bxor 0 binvert"),
+        "31 2 5 -1"
+    );
+    assert_eq!(
+        run("1 4 lshift 1 64 lshift 255 popcount -1 popcount"),
+        "16 0 8 64"
+    );
+    assert_eq!(run("-16 2 rshift -16 2 arshift"), "4611686018427387900 -4");
+    assert_eq!(run("0xFFFFFFFFFFFFFFFF 1 band"), "1");
+}
+
+#[test]
+fn bit_words_need_64_bit_ints() {
+    let mut interp = Interpreter::new();
+    for src in ["1.5 1 band", "1 \"a\" bor"] {
+        let error = interp.eval(src).unwrap_err();
+        assert!(
+            matches!(error.kind(), ErrorKind::TypeMismatch { .. }),
+            "{}",
+            src
+        );
+    }
+    let error = interp.eval("2 70 pow 1 band").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Custom(_)));
+}
+
+#[test]
+fn printing_in_other_radixes() {
+    assert_eq!(printed("255 .hex 5 .bin"), "ff\n101\n");
+    assert_eq!(printed("-1 .hex"), "ffffffffffffffff\n");
+    assert_eq!(printed("16 base! 255 . 10 base! 255 ."), "ff\n255\n");
+}
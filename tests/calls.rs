@@ -0,0 +1,70 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn top(interp: &mut Interpreter) -> Type {
+    interp.pop().unwrap()
+}
+
+#[test]
+fn tail_recursion_runs_in_constant_space() {
+    let mut interp = Interpreter::new();
+    interp
+        .eval(": down dup 0 > if 1 - down then ; 1000000 down")
+        .unwrap();
+    assert!(matches!(top(&mut interp), Type::Int(0)));
+}
+
+#[test]
+fn deep_recursion_is_refused() {
+    let mut interp = Interpreter::new();
+    let error = interp
+        .eval(": deep dup 0 > if 1 - deep 1 + then ; 20000 deep")
+        .unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::DepthExceeded(10000)));
+
+    interp.set_max_depth(100);
+    interp.stack.clear();
+    interp.eval("50 deep").unwrap();
+    assert!(matches!(top(&mut interp), Type::Int(50)));
+    let error = interp.eval("200 deep").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::DepthExceeded(100)));
+}
+
+#[test]
+fn nested_quotations_are_limited() {
+    // Nesting that deep needs the main thread's stack size in debug builds,
+    // test threads get less
+    let nest = || {
+        let mut interp = Interpreter::new();
+        let error = interp.eval(": nest [: nest ;] call ; nest").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::DepthExceeded(1000)));
+    };
+    std::thread::Builder::new()
+        .stack_size(8 * 1024 * 1024)
+        .spawn(nest)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn backtraces_show_each_call() {
+    let mut interp = Interpreter::new();
+    let error = interp
+        .eval(": a 1 0 / ; : b a 1 + ; : c b 1 + ;\nc")
+        .unwrap_err();
+    assert_eq!(error.word(), Some("/"));
+    let words: Vec<&str> = error.backtrace().iter().map(|f| f.word.as_str()).collect();
+    assert_eq!(words, ["a", "b", "c"]);
+    assert_eq!(error.backtrace()[2].span.line, 2);
+}
+
+#[test]
+fn backtraces_count_tail_calls() {
+    let mut interp = Interpreter::new();
+    let error = interp.eval(": a 1 0 / ; : b a ; : c b ;\nc").unwrap_err();
+    let backtrace = error.backtrace();
+    assert_eq!(backtrace.len(), 1);
+    assert_eq!(backtrace[0].word, "c");
+    assert_eq!(backtrace[0].span.line, 2);
+    assert_eq!(backtrace[0].omitted, 2);
+}
@@ -0,0 +1,41 @@
+use smorth::{Interpreter, Type};
+
+fn run(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+#[test]
+fn equality_works_on_any_values() {
+    assert_eq!(run("1 1 = 1 2 = 1 2 <> 1 1 !="), "true false true false");
+    assert_eq!(run("\"a\" \"a\" = [ 1 2 ] [ 1 2 ] ="), "true true");
+    assert_eq!(run("1 \"1\" = 1 \"1\" <>"), "false true");
+}
+
+#[test]
+fn numbers_compare_by_exact_value() {
+    assert_eq!(run("1 1.0 = 1/2 0.5 = 0.5d 1/2 ="), "true true true");
+    assert_eq!(run("9007199254740993 9007199254740992.0 ="), "false");
+    assert_eq!(run("9007199254740993 9007199254740992.0 >"), "true");
+    assert_eq!(run("1/3 0.3333333333333333 >"), "true");
+}
+
+#[test]
+fn values_of_any_type_are_ordered() {
+    assert_eq!(run("\"apple\" \"banana\" <"), "true");
+    assert_eq!(
+        run("[ 3 \"b\" 1 [ 2 ] 1 2 < ] sort"),
+        "[true, 1, 3, \"b\", [2]]"
+    );
+    assert_eq!(run("1 2 compare 2 2 compare \"b\" \"a\" compare"), "-1 0 1");
+    assert_eq!(run("3 7 min 3 7 max \"b\" \"a\" min"), "3 7 \"a\"");
+}
+
+#[test]
+fn ranges_and_zero_tests() {
+    assert_eq!(run("5 1 10 between 10 1 10 between"), "true true");
+    assert_eq!(run("5 1 10 within 10 1 10 within"), "true false");
+    assert_eq!(run("0 0= 1 0= -1 0< 0 0<"), "true false true false");
+}
@@ -0,0 +1,66 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn run(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+fn syntax_error(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    match interp.eval(src).unwrap_err().kind() {
+        ErrorKind::Syntax(message) => message.clone(),
+        kind => panic!("{:?} is not a syntax error", kind),
+    }
+}
+
+#[test]
+fn if_picks_a_branch() {
+    assert_eq!(run("1 2 < if 10 else 20 then"), "10");
+    assert_eq!(run("2 1 < if 10 else 20 then"), "20");
+    assert_eq!(run("2 1 < if 10 then 30"), "30");
+    assert_eq!(
+        run(": sign dup 0 < if drop -1 else 0 > if 1 else 0 then then ; -5 sign 0 sign 7 sign"),
+        "-1 0 1"
+    );
+}
+
+#[test]
+fn if_needs_a_bool() {
+    let mut interp = Interpreter::new();
+    let error = interp.eval("1 if 2 then").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::TypeMismatch { .. }));
+}
+
+#[test]
+fn unbalanced_control_words_are_refused() {
+    assert_eq!(syntax_error("then"), "'then' without matching 'if'");
+    assert_eq!(syntax_error(": f 1 if 2 ;"), "Unterminated 'if'");
+    assert_eq!(syntax_error("loop"), "'loop' without matching 'do'");
+    assert_eq!(syntax_error("repeat"), "'repeat' without matching 'while'");
+    assert_eq!(syntax_error("leave"), "'leave' outside of a 'do' loop");
+}
+
+#[test]
+fn do_loops_count() {
+    assert_eq!(run("5 0 do i loop"), "0 1 2 3 4");
+    assert_eq!(run("10 0 do i 3 +loop"), "0 3 6 9");
+    assert_eq!(run("0 10 do i -3 +loop"), "10 7 4 1");
+    assert_eq!(run("0 0 ?do i loop"), "");
+    assert_eq!(
+        run("3 0 do 2 0 do j i loop loop"),
+        "0 0 0 1 1 0 1 1 2 0 2 1"
+    );
+    assert_eq!(run("10 0 do i i 4 = if leave then loop"), "0 1 2 3 4");
+}
+
+#[test]
+fn begin_loops_run_until_a_condition() {
+    assert_eq!(run("0 begin 1 + dup 5 = until"), "5");
+    assert_eq!(run("0 begin dup 3 < while 1 + repeat"), "3");
+    assert_eq!(
+        run(": count 0 begin dup 3 < while dup 1 + repeat ; count"),
+        "0 1 2 3"
+    );
+}
@@ -0,0 +1,62 @@
+use smorth::{Division, ErrorKind, Interpreter, Type};
+
+fn run_with(interp: &mut Interpreter, src: &str) -> String {
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    interp.stack.clear();
+    items.join(" ")
+}
+
+fn run(src: &str) -> String {
+    run_with(&mut Interpreter::new(), src)
+}
+
+#[test]
+fn division_truncates_by_default() {
+    assert_eq!(run("7 2 / -7 2 / 7 -2 /"), "3 -3 -3");
+    assert_eq!(run("7 2 mod -7 2 mod 7 -2 mod"), "1 -1 1");
+    assert_eq!(run("-7 2 /mod"), "-1 -3");
+}
+
+#[test]
+fn floored_division_rounds_down() {
+    assert_eq!(
+        run("floored-division -7 2 / -7 2 mod 7 -2 mod -7 2 /mod"),
+        "-4 1 -1 1 -4"
+    );
+    assert_eq!(run("floored-division truncated-division -7 2 /"), "-3");
+
+    let mut interp = Interpreter::new();
+    interp.set_division(Division::Floor);
+    assert_eq!(run_with(&mut interp, "-7 2 /"), "-4");
+}
+
+#[test]
+fn rem_follows_the_dividend() {
+    assert_eq!(run("-7 2 rem 7 -2 rem"), "-1 1");
+    assert_eq!(run("floored-division -7 2 rem"), "-1");
+}
+
+#[test]
+fn float_division() {
+    assert_eq!(run("7 2 f/"), "3.5");
+    assert_eq!(run("1.0 0 /"), "inf");
+}
+
+#[test]
+fn dividing_ints_by_zero_is_an_error() {
+    let mut interp = Interpreter::new();
+    for src in ["1 0 /", "1 0 mod", "1 0 rem", "1 0 /mod"] {
+        let error = interp.eval(src).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::DivisionByZero), "{}", src);
+    }
+}
+
+#[test]
+fn big_ints_divide_exactly() {
+    assert_eq!(
+        run("123456789012345678901234567890 3 /"),
+        "41152263004115226300411522630"
+    );
+    assert_eq!(run("-9223372036854775808 -1 /"), "9223372036854775808");
+}
@@ -0,0 +1,87 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn run(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+#[test]
+fn catch_gets_the_stack_from_before_try() {
+    assert_eq!(run("1 2 try 3 4 0 / catch end"), "1 2 \"Division by zero\"");
+    assert_eq!(run("1 try 2 throw catch end"), "1 2");
+    assert_eq!(run("try 5 catch 6 end"), "5");
+}
+
+#[test]
+fn finally_always_runs() {
+    assert_eq!(run("try 1 finally 2 end"), "1 2");
+    assert_eq!(run("try 1 throw catch drop 3 finally 4 end"), "3 4");
+
+    let mut interp = Interpreter::new();
+    let error = interp.eval("try 7 throw finally 8 end").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Thrown(Type::Int(7))));
+    assert!(matches!(interp.pop(), Ok(Type::Int(8))));
+}
+
+#[test]
+fn errors_in_finally_replace_pending_ones() {
+    let mut interp = Interpreter::new();
+    let error = interp.eval("try 1 throw finally 2 throw end").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Thrown(Type::Int(2))));
+}
+
+#[test]
+fn try_nests() {
+    assert_eq!(
+        run("try try 1 throw catch 10 + throw end catch 100 + end"),
+        "111"
+    );
+}
+
+#[test]
+fn leave_ends_the_loop_around_try() {
+    assert_eq!(
+        run(": g 5 0 do try i 2 = if leave then i catch end loop ; g"),
+        "0 1"
+    );
+    assert_eq!(
+        run("5 0 do try i 3 = if leave then catch end i loop"),
+        "0 1 2"
+    );
+    // From a nested try, from catch and from finally
+    assert_eq!(
+        run("5 0 do try try i 1 = if leave then catch end finally i end loop"),
+        "0 1"
+    );
+    assert_eq!(
+        run("5 0 do try i throw catch dup 2 = if leave then end loop"),
+        "0 1 2"
+    );
+    assert_eq!(
+        run("5 0 do try i 1 = if 7 throw then finally i 1 = if leave then end i loop 42"),
+        "0 42"
+    );
+    // Only the inner loop ends
+    assert_eq!(
+        run("2 0 do 3 0 do try i 1 = if leave then i catch end loop 9 loop"),
+        "0 9 0 9"
+    );
+}
+
+#[test]
+fn leave_outside_a_loop_is_refused() {
+    let mut interp = Interpreter::new();
+    for src in ["try leave catch end", "5 0 do [: leave ;] call loop"] {
+        let error = interp.eval(src).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Syntax(_)), "{}", src);
+    }
+}
+
+#[test]
+fn try_needs_catch_or_finally() {
+    let mut interp = Interpreter::new();
+    let error = interp.eval("try 1 end").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Syntax(_)));
+}
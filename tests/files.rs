@@ -0,0 +1,92 @@
+use smorth::{ErrorKind, Interpreter, Type};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A fresh directory for the files of one test
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("smorth-{}-{}", std::process::id(), test));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn stack(interp: &Interpreter) -> String {
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+fn syntax_error(interp: &mut Interpreter, path: &Path) -> String {
+    match interp.eval_file(path).unwrap_err().kind() {
+        ErrorKind::Syntax(message) => message.clone(),
+        kind => panic!("{:?} is not a syntax error", kind),
+    }
+}
+
+#[test]
+fn unterminated_literals_are_reported() {
+    let dir = scratch("literals");
+    let list = dir.join("list.sf");
+    fs::write(&list, "1 2 [ 3").unwrap();
+    let map = dir.join("map.sf");
+    fs::write(&map, "#[ \"a\" 1").unwrap();
+
+    let mut interp = Interpreter::new();
+    let message = syntax_error(&mut interp, &list);
+    assert_eq!(message, format!("Unterminated '[' in {}", list.display()));
+    let message = syntax_error(&mut interp, &map);
+    assert_eq!(message, format!("Unterminated '#[' in {}", map.display()));
+
+    // The literal doesn't carry over into the next eval
+    interp.stack.clear();
+    interp.eval("4 ]").unwrap_err();
+}
+
+#[test]
+fn literals_can_hold_an_include() {
+    let dir = scratch("include");
+    fs::write(dir.join("items.sf"), "4 5").unwrap();
+    let main = dir.join("main.sf");
+    fs::write(&main, "[ \"items.sf\" include ]").unwrap();
+
+    let mut interp = Interpreter::new();
+    interp.eval_file(&main).unwrap();
+    assert_eq!(stack(&interp), "[4, 5]");
+}
+
+#[test]
+fn require_evaluates_a_file_once() {
+    let dir = scratch("once");
+    fs::write(dir.join("lib.sf"), "1").unwrap();
+    let main = dir.join("main.sf");
+    fs::write(&main, "\"lib.sf\" require \"lib.sf\" require").unwrap();
+
+    let mut interp = Interpreter::new();
+    interp.eval_file(&main).unwrap();
+    assert_eq!(stack(&interp), "1");
+}
+
+#[test]
+fn failed_requires_are_tried_again() {
+    let dir = scratch("retry");
+    let lib = dir.join("lib.sf");
+    fs::write(&lib, "1 0 /").unwrap();
+
+    let mut interp = Interpreter::new();
+    interp.require_file(&lib).unwrap_err();
+    fs::write(&lib, ": answer 42 ;").unwrap();
+    interp.require_file(&lib).unwrap();
+    interp.eval("answer").unwrap();
+    assert!(matches!(interp.pop(), Ok(Type::Int(42))));
+}
+
+#[test]
+fn files_can_require_each_other() {
+    let dir = scratch("cycle");
+    fs::write(dir.join("a.sf"), "\"b.sf\" require : a 1 ;").unwrap();
+    fs::write(dir.join("b.sf"), "\"a.sf\" require : b 2 ;").unwrap();
+
+    let mut interp = Interpreter::new();
+    interp.eval_file(dir.join("a.sf")).unwrap();
+    interp.eval("a b").unwrap();
+    assert_eq!(stack(&interp), "1 2");
+}
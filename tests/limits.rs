@@ -0,0 +1,102 @@
+use smorth::{ErrorKind, Interpreter, Limits};
+use std::time::Duration;
+
+fn limited(limits: Limits) -> Interpreter {
+    let mut interp = Interpreter::sandboxed();
+    interp.set_limits(limits);
+    interp
+}
+
+fn limit_message(interp: &mut Interpreter, source: &str) -> String {
+    match interp.eval(source).unwrap_err().kind() {
+        ErrorKind::LimitExceeded(message) => message.clone(),
+        other => panic!("expected a limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn empty_quotations_use_up_instructions() {
+    let mut interp = limited(Limits {
+        max_instructions: Some(1000),
+        ..Limits::sandbox()
+    });
+    let message = limit_message(&mut interp, "100000000000 [: ;] times");
+    assert_eq!(message, "Instruction limit of 1000 exceeded");
+
+    let message = limit_message(&mut interp, "[ 1 2 3 ] 100000000000 [: ;] times");
+    assert_eq!(message, "Instruction limit of 1000 exceeded");
+}
+
+#[test]
+fn empty_quotations_use_up_time() {
+    let mut interp = limited(Limits {
+        max_instructions: None,
+        max_time: Some(Duration::from_millis(50)),
+        ..Limits::sandbox()
+    });
+    let message = limit_message(&mut interp, "100000000000 [: ;] times");
+    assert!(message.starts_with("Time limit"), "{}", message);
+}
+
+#[test]
+fn infinite_loops_stop() {
+    let mut interp = limited(Limits {
+        max_instructions: Some(10_000),
+        ..Limits::sandbox()
+    });
+    let message = limit_message(&mut interp, "begin 1 0 < until");
+    assert_eq!(message, "Instruction limit of 10000 exceeded");
+
+    // Each eval starts counting again
+    interp.eval("1 2 +").unwrap();
+    assert_eq!(interp.stack.len(), 1);
+}
+
+#[test]
+fn stack_depth_is_limited() {
+    let mut interp = limited(Limits::sandbox());
+    let message = limit_message(&mut interp, "begin 1 1 0 < until");
+    assert_eq!(message, "Stack depth limit of 10000 exceeded");
+}
+
+#[test]
+fn memory_is_limited() {
+    let mut interp = limited(Limits {
+        max_memory: Some(1024 * 1024),
+        ..Limits::sandbox()
+    });
+    let message = limit_message(&mut interp, "\"x\" 30 0 do dup .. loop");
+    assert_eq!(message, "Memory limit of 1048576 bytes exceeded");
+
+    let message = limit_message(&mut interp, "\"xxxxxxxx\" 20 0 do dup .. loop \"big\" !");
+    assert_eq!(message, "Memory limit of 1048576 bytes exceeded");
+}
+
+#[test]
+fn output_is_limited() {
+    let mut interp = limited(Limits {
+        max_output_bytes: Some(100),
+        ..Limits::sandbox()
+    });
+    interp.set_output(std::io::sink());
+    let message = limit_message(&mut interp, "1000 0 do i . loop");
+    assert_eq!(message, "Output limit of 100 bytes exceeded");
+}
+
+#[test]
+fn try_cannot_catch_limits() {
+    let mut interp = limited(Limits {
+        max_instructions: Some(1000),
+        ..Limits::sandbox()
+    });
+    let message = limit_message(&mut interp, "try begin 1 0 < until catch drop end");
+    assert_eq!(message, "Instruction limit of 1000 exceeded");
+}
+
+#[test]
+fn sandbox_leaves_out_file_words() {
+    let mut interp = Interpreter::sandboxed();
+    assert!(!interp.is_defined("include"));
+    assert!(!interp.is_defined("require"));
+    assert!(interp.eval("\"x.sf\" include").is_err());
+}
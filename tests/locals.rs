@@ -0,0 +1,51 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn run(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+#[test]
+fn locals_take_the_arguments() {
+    assert_eq!(
+        run(": hyp { a b -- c } a@ a@ * b@ b@ * + sqrt ; 3 4 hyp"),
+        "5"
+    );
+    assert_eq!(run(": swap2 { a b } b@ a@ ; 1 2 swap2"), "2 1");
+    assert_eq!(run(": inc { n } n@ 1 + n! n@ ; 41 inc"), "42");
+}
+
+#[test]
+fn locals_belong_to_each_call() {
+    assert_eq!(
+        run(": fact { n } n@ 1 > if n@ 1 - fact n@ * else 1 then ; 20 fact"),
+        "2432902008176640000"
+    );
+    assert_eq!(
+        run(": inner { a } a@ 10 * ; : outer { a } a@ inner a@ + ; 3 outer"),
+        "33"
+    );
+}
+
+#[test]
+fn locals_shadow_variables() {
+    assert_eq!(run("1 x! : f { x } x@ ; 5 f x@"), "5 1");
+}
+
+#[test]
+fn bad_locals_are_refused() {
+    let mut interp = Interpreter::new();
+    for src in [": q { a } [: a@ ;] ;", ": r { a a } ;", ": s { a! } ;"] {
+        let error = interp.eval(src).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Syntax(_)), "{}", src);
+    }
+}
+
+#[test]
+fn missing_arguments_underflow() {
+    let mut interp = Interpreter::new();
+    let error = interp.eval(": f { a b } a@ ; 1 f").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::StackUnderflow));
+}
@@ -0,0 +1,65 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn run(interp: &mut Interpreter, src: &str) -> String {
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    interp.stack.clear();
+    items.join(" ")
+}
+
+const DRAWS: &str = "random 1 6 random-int [ 1 2 3 4 5 ] shuffle [ 1 2 3 ] choice";
+
+#[test]
+fn seeding_repeats_the_numbers() {
+    let mut interp = Interpreter::new();
+    let first = run(&mut interp, &format!("42 seed {}", DRAWS));
+    assert_eq!(run(&mut interp, &format!("42 seed {}", DRAWS)), first);
+
+    let mut other = Interpreter::new();
+    other.set_seed(42);
+    assert_eq!(run(&mut other, DRAWS), first);
+
+    assert_ne!(run(&mut other, &format!("43 seed {}", DRAWS)), first);
+}
+
+#[test]
+fn numbers_stay_in_range() {
+    let mut interp = Interpreter::new();
+    interp.set_seed(7);
+    for _ in 0..1000 {
+        interp
+            .eval("random 1 6 random-int -2 2 random-int")
+            .unwrap();
+        let Ok(Type::Int(small)) = interp.pop() else {
+            panic!()
+        };
+        let Ok(Type::Int(die)) = interp.pop() else {
+            panic!()
+        };
+        let Ok(Type::Float(f)) = interp.pop() else {
+            panic!()
+        };
+        assert!((-2..=2).contains(&small));
+        assert!((1..=6).contains(&die));
+        assert!((0.0..1.0).contains(&f));
+    }
+    assert_eq!(run(&mut interp, "5 5 random-int"), "5");
+}
+
+#[test]
+fn shuffle_keeps_the_items() {
+    let mut interp = Interpreter::new();
+    assert_eq!(
+        run(&mut interp, "[ 5 3 1 4 2 ] shuffle sort [ ] shuffle"),
+        "[1, 2, 3, 4, 5] []"
+    );
+}
+
+#[test]
+fn empty_choices_are_refused() {
+    let mut interp = Interpreter::new();
+    for src in ["[ ] choice", "6 1 random-int"] {
+        let error = interp.eval(src).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Custom(_)), "{}", src);
+    }
+}
@@ -0,0 +1,51 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn run(src: &str) -> String {
+    let mut interp = Interpreter::new();
+    interp.eval(src).unwrap();
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+#[test]
+fn summaries_stay_exact() {
+    assert_eq!(run("[ 1 2 3 4 ] sum [ 1 2 3 4 ] product"), "10 24");
+    assert_eq!(
+        run("[ 1 2 ] mean [ 0.1d 0.2d ] sum [ 1.5 2 ] sum"),
+        "3/2 0.3 3.5"
+    );
+    assert_eq!(run("[ 3 1 2 ] median [ 4 1 2 3 ] median"), "2 5/2");
+    assert_eq!(
+        run("[ 3 3 1 2 2 ] mode [ 5 1 9 ] min-of [ 5 1 9 ] max-of"),
+        "2 1 9"
+    );
+}
+
+#[test]
+fn spread() {
+    assert_eq!(run("[ 2 4 4 4 5 5 7 9 ] variance"), "32/7");
+    assert_eq!(run("[ 2 4 4 4 5 5 7 9 ] stddev"), "2.138089935299395");
+    assert_eq!(run("[ 10 20 30 40 ] 90 percentile"), "37");
+    assert_eq!(
+        run("[ 10 20 30 40 ] 0 percentile [ 10 20 30 40 ] 100 percentile"),
+        "10 40"
+    );
+}
+
+#[test]
+fn histogram_and_linreg() {
+    assert_eq!(run("[ 1 2 2 3 9 10 ] 3 histogram"), "[4, 0, 2]");
+    assert_eq!(run("[ 1 2 3 ] [ 1 2 2 ] linreg"), "1/2 2/3");
+    assert_eq!(run("[ 0 1 2 ] [ 1 3 5 ] linreg"), "2 1");
+}
+
+#[test]
+fn too_few_values_are_refused() {
+    let mut interp = Interpreter::new();
+    for src in ["[ ] mean", "[ ] median", "[ 1 ] variance", "[ ] max-of"] {
+        let error = interp.eval(src).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Custom(_)), "{}", src);
+    }
+    let error = interp.eval("[ 1 \"a\" ] sum").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::TypeMismatch { .. }));
+}
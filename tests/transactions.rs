@@ -0,0 +1,77 @@
+use smorth::{ErrorKind, Interpreter, Type};
+
+fn stack(interp: &Interpreter) -> String {
+    let items: Vec<String> = interp.stack.iter().map(Type::repr).collect();
+    items.join(" ")
+}
+
+#[test]
+fn failed_eval_keeps_partial_work_by_default() {
+    let mut interp = Interpreter::new();
+    assert!(interp.eval("1 2 : sq dup * ; 3 0 /").is_err());
+    assert_eq!(stack(&interp), "1 2");
+    assert!(interp.is_defined("sq"));
+}
+
+#[test]
+fn transactional_eval_rolls_everything_back() {
+    let mut interp = Interpreter::new();
+    interp.set_transactional(true);
+    interp.eval("1 \"before\" x!").unwrap();
+
+    let result = interp.eval("2 3 \"after\" x! : sq dup * ; vocabulary v 1 0 /");
+    assert!(matches!(
+        result.unwrap_err().kind(),
+        ErrorKind::DivisionByZero
+    ));
+    assert_eq!(stack(&interp), "1");
+    assert!(matches!(interp.get_var("x"), Some(Type::String(s)) if s == "before"));
+    assert!(!interp.is_defined("sq"));
+    assert!(interp.eval("in v").is_err());
+
+    // A definition open before the failed eval is open again as it was
+    interp.eval("2 : half").unwrap();
+    assert!(interp.eval("2 / ; 1 0 /").is_err());
+    assert!(!interp.is_defined("half"));
+    interp.eval("; 3 +").unwrap();
+    assert!(interp.is_defined("half"));
+    assert_eq!(stack(&interp), "1 5");
+}
+
+#[test]
+fn restore_goes_back_to_a_snapshot() {
+    let mut interp = Interpreter::new();
+    let before = interp.snapshot();
+    interp.eval("1 2 : half-done").unwrap();
+    interp.restore(before);
+    assert!(interp.stack.is_empty());
+    interp.eval("3").unwrap();
+    assert_eq!(stack(&interp), "3");
+}
+
+#[test]
+fn restoring_an_older_snapshot_can_be_undone() {
+    let mut interp = Interpreter::new();
+    let a = interp.snapshot();
+    interp.eval(": foo 42 ;").unwrap();
+    let b = interp.snapshot();
+    interp.restore(a);
+    assert!(!interp.is_defined("foo"));
+    interp.restore(b);
+    interp.eval("foo").unwrap();
+    assert_eq!(stack(&interp), "42");
+}
+
+#[test]
+fn quotations_outlive_a_restore() {
+    let mut interp = Interpreter::new();
+    let before = interp.snapshot();
+    interp.eval(": sq dup * ; [: sq ;]").unwrap();
+    let quotation = interp.pop().unwrap();
+    interp.restore(before);
+
+    interp.push(7);
+    interp.call_quotation(&quotation).unwrap();
+    assert_eq!(stack(&interp), "49");
+    assert!(!interp.is_defined("sq"));
+}